@@ -0,0 +1,145 @@
+use geo_types::{LineString, Coordinate};
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::ops::{AddAssign, SubAssign};
+
+use super::{Segment, segment_normal, join_with, extract_regions, OffsetError};
+
+/// An ellipse centered on the origin with the semi-axes `a` and `b`, rotated counter-clockwise by the angle given as its sine and cosine.
+struct Ellipse<N> {
+    a: N,
+    b: N,
+    sin: N,
+    cos: N,
+}
+
+impl<N> Ellipse<N> where N: Num + Copy + NumCast + PartialOrd + Float {
+    /// Returns the point of the ellipse that is farthest in the direction of the given angle.
+    fn support(&self, angle: N) -> Coordinate<N> {
+        let (x, y) = (angle.cos(), angle.sin());
+        // rotate the direction into the frame of the ellipse
+        let (x, y) = (x * self.cos + y * self.sin, y * self.cos - x * self.sin);
+        let len = ((self.a * x) * (self.a * x) + (self.b * y) * (self.b * y)).sqrt();
+        let (x, y) = (self.a * self.a * x / len, self.b * self.b * y / len);
+        Coordinate {
+            x: x * self.cos - y * self.sin,
+            y: y * self.cos + x * self.sin,
+        }
+    }
+}
+
+/// Expands or shrinks the given polygon by an ellipse instead of a circle. Every edge is moved to the point of the ellipse that is
+/// farthest in the direction of the edge's normal, and corners are rounded with elliptical arcs.
+///
+/// # Arguments
+///
+/// * `polygon` - A polygon to shrink or expand. It has to be closed (the last coordinate has to be the same as the first coordinate)
+/// * `offset_x` - The semi-axis of the ellipse along the (rotated) x axis. A positive number expands the polygon, a negative number shrinks it.
+/// * `offset_y` - The semi-axis of the ellipse along the (rotated) y axis. It has to have the same sign as `offset_x`, neither of them may be zero.
+/// * `rotation` - The counter-clockwise rotation of the ellipse in radians, `0` for an axis-aligned ellipse.
+/// * `arcdetail` - Defines how many points should be added in a sharp corner. This number is the number of vertices inserted if it's a full ellipse.
+///
+/// Returns the same as [`offset_polygon`](fn.offset_polygon.html). Fails with `OffsetError::InvalidArgument` if the semi-axes
+/// have different signs or one of them is zero.
+pub fn offset_polygon_anisotropic<N>(polygon: &LineString<N>, offset_x: N, offset_y: N, rotation: N, arcdetail: N) -> Result<Vec<LineString<N>>, OffsetError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    if offset_x == N::zero() || offset_y == N::zero() || offset_x.is_sign_negative() != offset_y.is_sign_negative() {
+        return Err(OffsetError::InvalidArgument("the semi-axes of the ellipse have to be non-zero and have the same sign".to_string()));
+    }
+    if polygon.0.is_empty() {
+        return Ok(vec![LineString(Vec::new())]);
+    }
+    let arcstep = N::from_f32(2.0).unwrap() * N::PI() / arcdetail;
+    let inwards = offset_x.is_sign_negative();
+    let ellipse = Ellipse {
+        a: offset_x.abs(),
+        b: offset_y.abs(),
+        sin: rotation.sin(),
+        cos: rotation.cos(),
+    };
+    let displacement = |angle: N| {
+        let support = ellipse.support(angle);
+        if inwards {
+            Coordinate { x: -support.x, y: -support.y }
+        } else {
+            support
+        }
+    };
+
+    let lines: Vec<Segment<N>> = (0..(polygon.0.len()-1)).filter_map(|idx| {
+        let (p0, p1) = (polygon.0[idx], polygon.0[idx+1]);
+        segment_normal(p0, p1).map(|(normal, _)| {
+            let displacement = displacement(normal.y.atan2(normal.x));
+            Segment {
+                p0: Coordinate {
+                    x: p0.x + displacement.x,
+                    y: p0.y + displacement.y,
+                },
+                p1: Coordinate {
+                    x: p1.x + displacement.x,
+                    y: p1.y + displacement.y,
+                },
+                p1_orig: p1,
+                normal,
+                offset: offset_x,
+                edge: idx,
+            }
+        })
+    }).collect();
+
+    let mut connected = Vec::new();
+    for idx in 0..lines.len() {
+        let (line0, line1) = (&lines[idx], &lines[(idx+1) % lines.len()]);
+        connected.extend_from_slice(&[line0.p0, line0.p1]);
+        join_with(&mut connected, line0, line1, inwards, arcstep, displacement);
+    }
+    connected.push(connected[0]);
+
+    Ok(extract_regions(connected)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn bounds(line: &LineString<f64>) -> (f64, f64, f64, f64) {
+        line.0.iter().fold((f64::MAX, f64::MAX, f64::MIN, f64::MIN), |(x0, y0, x1, y1), p| (x0.min(p.x), y0.min(p.y), x1.max(p.x), y1.max(p.y)))
+    }
+    #[test]
+    fn rectangle_extend() {
+        let input = LineString(vec![
+            Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 10.0, y: 0.0 }, Coordinate { x: 10.0, y: 10.0 }, Coordinate { x: 0.0, y: 10.0 }, Coordinate { x: 0.0, y: 0.0 },
+        ]);
+        let result = offset_polygon_anisotropic(&input, 2.0, 1.0, 0.0, 20.0).unwrap();
+        assert!(result.len() == 1, "rectangle input should result in one polygon");
+        let (x0, y0, x1, y1) = bounds(&result[0]);
+        assert!((x0 + 2.0).abs() < 1e-9 && (y0 + 1.0).abs() < 1e-9 && (x1 - 12.0).abs() < 1e-9 && (y1 - 11.0).abs() < 1e-9, "Incorrect anisotropic offsetting for rectangle");
+        assert!(result[0].0.iter().all(|p| {
+            // corner points have to be on the ellipse around the closest corner
+            let (cx, cy) = (p.x.clamp(0.0, 10.0), p.y.clamp(0.0, 10.0));
+            let (dx, dy) = ((p.x - cx) / 2.0, (p.y - cy) / 1.0);
+            (p.x >= 0.0 && p.x <= 10.0) || (p.y >= 0.0 && p.y <= 10.0) || (dx * dx + dy * dy - 1.0).abs() < 1e-9
+        }), "Corner arcs have to be elliptical");
+
+        let result = offset_polygon_anisotropic(&input, 2.0, 1.0, f64::FRAC_PI_2(), 20.0).unwrap();
+        let (x0, y0, x1, y1) = bounds(&result[0]);
+        assert!((x0 + 1.0).abs() < 1e-9 && (y0 + 2.0).abs() < 1e-9 && (x1 - 11.0).abs() < 1e-9 && (y1 - 12.0).abs() < 1e-9, "Incorrect offsetting with rotated ellipse");
+    }
+    #[test]
+    fn rectangle_contract() {
+        let input = LineString(vec![
+            Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 10.0, y: 0.0 }, Coordinate { x: 10.0, y: 10.0 }, Coordinate { x: 0.0, y: 10.0 }, Coordinate { x: 0.0, y: 0.0 },
+        ]);
+        let result = offset_polygon_anisotropic(&input, -2.0, -1.0, 0.0, 20.0).unwrap();
+        assert!(result.len() == 1, "rectangle input should result in one polygon");
+        assert!(result[0].0.iter().zip(vec![
+            Coordinate { x: 8.0, y: 1.0 }, Coordinate { x: 8.0, y: 9.0 }, Coordinate { x: 2.0, y: 9.0 }, Coordinate { x: 2.0, y: 1.0 }, Coordinate { x: 8.0, y: 1.0 },
+        ]).all(|(p0, p1)| (p0.x - p1.x).abs() < 1e-9 && (p0.y - p1.y).abs() < 1e-9), "Incorrect anisotropic offsetting for rectangle");
+    }
+    #[test]
+    fn invalid_semi_axes() {
+        let input = LineString(vec![
+            Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 10.0, y: 0.0 }, Coordinate { x: 10.0, y: 10.0 }, Coordinate { x: 0.0, y: 0.0 },
+        ]);
+        assert!(matches!(offset_polygon_anisotropic(&input, 2.0, -1.0, 0.0, 20.0), Err(OffsetError::InvalidArgument(_))), "Semi-axes with different signs have to be rejected");
+        assert!(matches!(offset_polygon_anisotropic(&input, 0.0, 0.0, 0.0, 20.0), Err(OffsetError::InvalidArgument(_))), "Zero semi-axes have to be rejected");
+    }
+}
@@ -0,0 +1,111 @@
+use geo_types::{LineString, Coordinate};
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::ops::{AddAssign, SubAssign};
+
+use super::{offset_polygon_with_provenance, Provenance, Origin, OffsetError};
+
+/// A value attached to the vertices of a polygon, like an elevation (Z), a measure (M) or a feed rate.
+pub trait VertexAttribute<N>: Clone {
+    /// Returns the value at position `t` between `self` (at `0`) and `other` (at `1`).
+    fn interpolate(&self, other: &Self, t: N) -> Self;
+}
+
+impl VertexAttribute<f32> for f32 {
+    fn interpolate(&self, other: &f32, t: f32) -> f32 {
+        self + (other - self) * t
+    }
+}
+
+impl VertexAttribute<f64> for f64 {
+    fn interpolate(&self, other: &f64, t: f64) -> f64 {
+        self + (other - self) * t
+    }
+}
+
+impl<N, A, B> VertexAttribute<N> for (A, B) where N: Copy, A: VertexAttribute<N>, B: VertexAttribute<N> {
+    fn interpolate(&self, other: &(A, B), t: N) -> (A, B) {
+        (self.0.interpolate(&other.0, t), self.1.interpolate(&other.1, t))
+    }
+}
+
+/// A polygon together with the attributes of each of its coordinates.
+pub type LineStringWithAttributes<N, A> = (LineString<N>, Vec<A>);
+
+/// Like [`offset_polygon`](fn.offset_polygon.html), but carries attributes of the input vertices over to the result. Vertices
+/// that are offset from an input edge get values interpolated along that edge (which is a copy of the value of the input vertex
+/// for the end points of the edge). Vertices of a corner arc inherit the value of their corner. Intersections get the average of
+/// the values on both of the crossing parts, so the result doesn't depend on which one is found first.
+///
+/// # Arguments
+///
+/// * `polygon` - A polygon to shrink or expand. It has to be closed (the last coordinate has to be the same as the first coordinate)
+/// * `attributes` - One value per coordinate of `polygon`.
+/// * `offset` - A positive number expands the polygon, a negative number shrinks it.
+/// * `arcdetail` - Defines how many points should be added in a sharp corner. This number is the number of vertices inserted if it's a full circle.
+///
+/// Returns the same polygons as `offset_polygon`, each with one value per coordinate. Fails with `OffsetError::InvalidArgument` if
+/// the number of attributes doesn't match the number of coordinates.
+pub fn offset_polygon_with_attributes<N, A>(polygon: &LineString<N>, attributes: &[A], offset: N, arcdetail: N) -> Result<Vec<LineStringWithAttributes<N, A>>, OffsetError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign,
+              A: VertexAttribute<N> {
+    if attributes.len() != polygon.0.len() {
+        return Err(OffsetError::InvalidArgument(format!("expected {} attributes, one per coordinate, got {}", polygon.0.len(), attributes.len())));
+    }
+    let along_edge = |edge: usize, p: Coordinate<N>| {
+        let (p0, p1) = (polygon.0[edge], polygon.0[edge+1]);
+        let (dx, dy) = (p1.x - p0.x, p1.y - p0.y);
+        let t = ((p.x - p0.x) * dx + (p.y - p0.y) * dy) / (dx * dx + dy * dy);
+        if t <= N::zero() {
+            attributes[edge].clone()
+        } else if t >= N::one() {
+            attributes[edge+1].clone()
+        } else {
+            attributes[edge].interpolate(&attributes[edge+1], t)
+        }
+    };
+    let value = |origin: Origin, p: Coordinate<N>| match origin {
+        Origin::Edge(edge) => along_edge(edge, p),
+        Origin::Corner(vertex) => attributes[vertex].clone(),
+    };
+    Ok(offset_polygon_with_provenance(polygon, offset, arcdetail)?.into_iter().map(|(line, provenance)| {
+        let values = line.0.iter().zip(provenance).map(|(p, provenance)| match provenance {
+            Provenance::Edge(edge) => along_edge(edge, *p),
+            Provenance::Corner(vertex) => attributes[vertex].clone(),
+            Provenance::Intersection(origin0, origin1) => value(origin0, *p).interpolate(&value(origin1, *p), N::from_f32(0.5).unwrap()),
+        }).collect();
+        (line, values)
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn elevation() {
+        let input: LineString<f64> = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0), (0.0, 0.0)].into();
+        let z = [0.0, 10.0, 20.0, 30.0, 0.0];
+        let result = offset_polygon_with_attributes(&input, &z, 1.0, 8.0).unwrap();
+        assert!(result.len() == 1 && result[0].0.0.len() == result[0].1.len(), "Every vertex needs an attribute");
+        assert!(result[0].0.0.iter().zip(&result[0].1).all(|(p, z)| {
+            if p.x < 0.0 && p.y < 0.0 { // arc around the first corner
+                *z == 0.0
+            } else if p.y < 0.0 && p.x <= 10.0 { // along the first edge
+                (z - p.x).abs() < 1e-12
+            } else {
+                true
+            }
+        }), "Incorrect attributes of expanded square");
+
+        let result = offset_polygon_with_attributes(&input, &z, -1.0, 8.0).unwrap();
+        let corner = result[0].0.0.iter().position(|p| (p.x - 9.0).abs() < 1e-12 && (p.y - 1.0).abs() < 1e-12).unwrap();
+        assert!((result[0].1[corner] - 10.0).abs() < 1e-12, "Intersections have to average the values along both of their edges");
+        assert!(matches!(offset_polygon_with_attributes(&input, &z[1..], 1.0, 8.0), Err(OffsetError::InvalidArgument(_))), "Missing attributes have to be reported");
+    }
+    #[test]
+    fn pairs() {
+        let input: LineString<f64> = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 0.0)].into();
+        let zm = [(1.0, 5.0), (2.0, 6.0), (3.0, 7.0), (1.0, 5.0)];
+        let result = offset_polygon_with_attributes(&input, &zm, 0.5, 8.0).unwrap();
+        assert!(result[0].1.iter().all(|(z, m)| (m - z - 4.0).abs() < 1e-12), "Each part of the attribute has to be interpolated");
+    }
+}
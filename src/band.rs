@@ -0,0 +1,86 @@
+use geo_types::{Coordinate, MultiPolygon};
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::ops::{AddAssign, SubAssign};
+
+use super::{offset_polygons, extract_filled_regions, assemble_polygons, FillRule, OffsetError};
+
+/// Returns the rings of the polygons in their orientation (counter-clockwise exteriors and clockwise holes) or reversed.
+fn rings<N>(polygons: &MultiPolygon<N>, reverse: bool) -> Vec<Vec<Coordinate<N>>>
+        where N: Num + Copy + NumCast + PartialOrd {
+    polygons.0.iter().flat_map(|polygon| std::iter::once(polygon.exterior()).chain(polygon.interiors())).map(|ring| {
+        let mut ring = ring.0.clone();
+        if reverse {
+            ring.reverse();
+        }
+        ring
+    }).collect()
+}
+
+/// Calculates the band between two offsets of the polygons, like a halo around them or a clearance ring. For example, the
+/// offsets `-d` and `d` result in a band of width `2d` centered on the outline.
+///
+/// # Arguments
+///
+/// * `polygons` - A polygon or multipolygon. Exteriors and holes may have any orientation.
+/// * `inner` - The offset of the inner boundary of the band. A positive number expands the polygons, a negative number shrinks them.
+/// * `outer` - The offset of the outer boundary of the band. It has to be greater than `inner`.
+/// * `arcdetail` - Defines how many points should be added in a sharp corner. This number is the number of vertices inserted if it's a full circle.
+///
+/// Returns the band as polygons with holes, with counter-clockwise exteriors and clockwise holes. Where the inner offset vanishes,
+/// the band has no hole; where it splits up, the band gets one hole per part. Fails with `OffsetError::InvalidArgument` if an offset
+/// isn't finite or `outer` isn't greater than `inner`, and otherwise with the same error as [`offset_polygon`](fn.offset_polygon.html).
+pub fn offset_band<N, G>(polygons: G, inner: N, outer: N, arcdetail: N) -> Result<MultiPolygon<N>, OffsetError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign,
+              G: Into<MultiPolygon<N>> {
+    if !inner.is_finite() || !outer.is_finite() || outer <= inner {
+        return Err(OffsetError::InvalidArgument("the offsets have to be finite, and the outer one greater than the inner one".to_string()));
+    }
+    let polygons = polygons.into();
+    let arcstep = N::from_f32(2.0).unwrap() * N::PI() / arcdetail;
+    // both offsets are resolved separately, so that each of them has a winding number of 1 inside and 0 outside
+    let mut curves = rings(&offset_polygons(&polygons, outer, arcstep)?, false);
+    curves.extend(rings(&offset_polygons(&polygons, inner, arcstep)?, true));
+    let regions = extract_filled_regions(curves, FillRule::Positive, None)?;
+    Ok(MultiPolygon(assemble_polygons(regions.into_iter().map(|(region, _)| region.0).collect())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo_types::{LineString, Polygon};
+    fn ring(points: &[(f64, f64)]) -> LineString<f64> {
+        points.iter().map(|(x, y)| Coordinate { x: *x, y: *y }).collect()
+    }
+    fn area(ring: &LineString<f64>) -> f64 {
+        ring.0.windows(2).map(|p| p[0].x * p[1].y - p[1].x * p[0].y).sum::<f64>() / 2.0
+    }
+    #[test]
+    fn square() {
+        let input = Polygon::new(ring(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]), vec![]);
+        let result = offset_band(input.clone(), -1.0, 1.0, 16.0).unwrap();
+        assert!(result.0.len() == 1 && result.0[0].interiors().len() == 1, "Band around a square should be one polygon with a hole");
+        let hole = &result.0[0].interiors()[0];
+        assert!((area(hole) + 64.0).abs() < 1e-9, "The hole has to be the inner offset in clockwise order");
+        let outer = area(result.0[0].exterior());
+        assert!(outer > 143.0 && outer < 144.0, "The exterior has to be the outer offset");
+
+        let result = offset_band(input, -6.0, 1.0, 16.0).unwrap();
+        assert!(result.0.len() == 1 && result.0[0].interiors().is_empty(), "Band without inner offset should not have a hole");
+    }
+    #[test]
+    fn invalid_offsets() {
+        let input = Polygon::new(ring(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]), vec![]);
+        assert!(matches!(offset_band(input.clone(), 1.0, -1.0, 16.0), Err(OffsetError::InvalidArgument(_))), "Swapped offsets have to be rejected");
+        assert!(matches!(offset_band(input.clone(), 1.0, 1.0, 16.0), Err(OffsetError::InvalidArgument(_))), "Equal offsets have to be rejected");
+        assert!(matches!(offset_band(input, f64::NAN, 1.0, 16.0), Err(OffsetError::InvalidArgument(_))), "Offsets that are not a number have to be rejected");
+    }
+    #[test]
+    fn split() {
+        // two squares connected by a narrow bridge, the inner offset falls apart into two parts
+        let input = Polygon::new(ring(&[(0.0, 0.0), (4.0, 0.0), (4.0, 1.5), (6.0, 1.5), (6.0, 0.0), (10.0, 0.0), (10.0, 4.0), (6.0, 4.0), (6.0, 2.5), (4.0, 2.5), (4.0, 4.0), (0.0, 4.0)]), vec![]);
+        let result = offset_band(input, -1.0, 0.5, 16.0).unwrap();
+        assert!(result.0.len() == 1 && result.0[0].interiors().len() == 2, "Band with a split inner offset should have two holes");
+        // the shrunk squares bulge a little into the bridge between the arcs around its corners
+        assert!(result.0[0].interiors().iter().all(|hole| area(hole) > -4.1 && area(hole) < -4.0), "Incorrect holes of the band");
+    }
+}
@@ -0,0 +1,220 @@
+use geo_types::{LineString, Coordinate, MultiPolygon};
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::ops::{AddAssign, SubAssign};
+
+use super::{offset_rings, FillRule, OffsetError};
+
+/// A segment of a Bezier contour.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BezierSegment<N: Num + Copy + NumCast + PartialOrd> {
+    Line {
+        start: Coordinate<N>,
+        end: Coordinate<N>,
+    },
+    Quadratic {
+        start: Coordinate<N>,
+        control: Coordinate<N>,
+        end: Coordinate<N>,
+    },
+    Cubic {
+        start: Coordinate<N>,
+        control1: Coordinate<N>,
+        control2: Coordinate<N>,
+        end: Coordinate<N>,
+    },
+}
+
+/// Stops the subdivision of degenerate curves (for example with coordinates that are not finite). Every level reduces the
+/// deviation by a factor of four, so finite curves only get here if the tolerance is far below the floating point precision.
+const MAX_DEPTH: usize = 24;
+
+fn midpoint<N>(a: Coordinate<N>, b: Coordinate<N>) -> Coordinate<N>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    let half = N::from_f32(0.5).unwrap();
+    Coordinate {
+        x: (a.x + b.x) * half,
+        y: (a.y + b.y) * half,
+    }
+}
+
+/// Returns the length of `a - 2b + c`, the second difference of three control points.
+fn second_difference<N>(a: Coordinate<N>, b: Coordinate<N>, c: Coordinate<N>) -> N
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    (a.x - b.x - b.x + c.x).hypot(a.y - b.y - b.y + c.y)
+}
+
+/// Adds the end points of a polyline that stays within `tolerance` of the segment, splitting curves in half until the bound on
+/// their distance from their chord is small enough. Returns `false` if the bound couldn't be reached within `MAX_DEPTH` levels.
+fn flatten_segment<N>(segment: &BezierSegment<N>, tolerance: N, depth: usize, points: &mut Vec<Coordinate<N>>) -> bool
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    match *segment {
+        BezierSegment::Line { end, .. } => {
+            points.push(end);
+            true
+        },
+        BezierSegment::Quadratic { start, control, end } => {
+            if second_difference(start, control, end) * N::from_f32(0.25).unwrap() <= tolerance {
+                points.push(end);
+                true
+            } else if depth >= MAX_DEPTH {
+                false
+            } else {
+                let (a, b) = (midpoint(start, control), midpoint(control, end));
+                let middle = midpoint(a, b);
+                flatten_segment(&BezierSegment::Quadratic { start, control: a, end: middle }, tolerance, depth + 1, points)
+                    && flatten_segment(&BezierSegment::Quadratic { start: middle, control: b, end }, tolerance, depth + 1, points)
+            }
+        },
+        BezierSegment::Cubic { start, control1, control2, end } => {
+            let deviation = second_difference(start, control1, control2).max(second_difference(control1, control2, end)) * N::from_f32(0.75).unwrap();
+            if deviation <= tolerance {
+                points.push(end);
+                true
+            } else if depth >= MAX_DEPTH {
+                false
+            } else {
+                let (a, b, c) = (midpoint(start, control1), midpoint(control1, control2), midpoint(control2, end));
+                let (d, e) = (midpoint(a, b), midpoint(b, c));
+                let middle = midpoint(d, e);
+                flatten_segment(&BezierSegment::Cubic { start, control1: a, control2: d, end: middle }, tolerance, depth + 1, points)
+                    && flatten_segment(&BezierSegment::Cubic { start: middle, control1: e, control2: c, end }, tolerance, depth + 1, points)
+            }
+        },
+    }
+}
+
+/// Converts a closed Bezier contour into a closed line string that is nowhere farther than `tolerance` from the contour.
+/// Curves are subdivided adaptively, so flat parts get few points and tight bends get many.
+///
+/// Fails with `OffsetError::InvalidArgument` if the tolerance isn't positive, or if it can't be reached because it is below the
+/// floating point precision of the coordinates or a coordinate is not finite.
+pub fn flatten_bezier<N>(contour: &[BezierSegment<N>], tolerance: N) -> Result<LineString<N>, OffsetError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    if tolerance <= N::zero() || tolerance.is_nan() {
+        return Err(OffsetError::InvalidArgument("the tolerance has to be positive".to_string()));
+    }
+    let mut points = Vec::new();
+    if let Some(first) = contour.first() {
+        points.push(match *first {
+            BezierSegment::Line { start, .. } | BezierSegment::Quadratic { start, .. } | BezierSegment::Cubic { start, .. } => start,
+        });
+    }
+    for segment in contour {
+        if !flatten_segment(segment, tolerance, 0, &mut points) {
+            return Err(OffsetError::InvalidArgument("the curves can't be flattened within the tolerance".to_string()));
+        }
+    }
+    if points.len() > 1 && points.first() != points.last() {
+        points.push(points[0]);
+    }
+    Ok(LineString(points))
+}
+
+/// Expands or shrinks shapes described by Bezier contours, like font glyphs or SVG paths. The contours are flattened with half
+/// of the tolerance, and the rounded corners of the offset get enough points to stay within the other half. Since moving a
+/// curve by at most `e` moves its offset by at most `e` as well, the result is within `tolerance` of the true offset curve.
+/// This holds for any offset, so `tolerance` is an absolute distance that doesn't have to be scaled with `offset`.
+///
+/// # Arguments
+///
+/// * `contours` - Closed contours, every segment has to start where the previous one ends. Counter-clockwise contours count as +1, clockwise contours as -1 (holes).
+/// * `offset` - A positive number expands the shapes, a negative number shrinks them.
+/// * `tolerance` - The maximum distance between the result and the true offset curve, has to be positive.
+///
+/// Returns the areas with a non-zero winding number as polygons with counter-clockwise exteriors and clockwise holes. Fails like
+/// [`flatten_bezier`](fn.flatten_bezier.html).
+pub fn offset_bezier<N>(contours: &[Vec<BezierSegment<N>>], offset: N, tolerance: N) -> Result<MultiPolygon<N>, OffsetError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let half = tolerance * N::from_f32(0.5).unwrap();
+    let mut rings = Vec::with_capacity(contours.len());
+    for contour in contours {
+        let ring = flatten_bezier(contour, half)?;
+        if ring.0.len() > 3 {
+            rings.push(ring);
+        }
+    }
+    // a chord of an arc with the angle `step` is at most `r * (1 - cos(step / 2))` away from the arc
+    let arcdetail = if half < offset.abs() {
+        let step = N::from_f32(2.0).unwrap() * (N::one() - half / offset.abs()).acos();
+        (N::from_f32(2.0).unwrap() * N::PI() / step).ceil().max(N::from_f32(4.0).unwrap())
+    } else {
+        N::from_f32(4.0).unwrap()
+    };
+    Ok(offset_rings(&rings, offset, arcdetail, FillRule::NonZero)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::point_in_polygon::segment_distance;
+    /// How far the curves of `circle` are outside of the true circle at most, relative to its radius.
+    const CIRCLE_ERROR: f64 = 2.7253e-4;
+    /// Four cubic curves approximating a counter-clockwise circle (within `CIRCLE_ERROR` of the radius).
+    fn circle(radius: f64) -> Vec<BezierSegment<f64>> {
+        let k = radius * 0.552_284_749_8;
+        let points = [(radius, 0.0), (0.0, radius), (-radius, 0.0), (0.0, -radius), (radius, 0.0)];
+        points.windows(2).map(|pair| {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            // tangents turn counter-clockwise
+            BezierSegment::Cubic {
+                start: Coordinate { x: x0, y: y0 },
+                control1: Coordinate { x: x0 - y0 / radius * k, y: y0 + x0 / radius * k },
+                control2: Coordinate { x: x1 + y1 / radius * k, y: y1 - x1 / radius * k },
+                end: Coordinate { x: x1, y: y1 },
+            }
+        }).collect()
+    }
+    fn point_at(segment: &BezierSegment<f64>, t: f64) -> Coordinate<f64> {
+        match *segment {
+            BezierSegment::Cubic { start, control1, control2, end } => {
+                let (a, b, c, d) = ((1.0 - t).powi(3), 3.0 * (1.0 - t).powi(2) * t, 3.0 * (1.0 - t) * t * t, t.powi(3));
+                Coordinate { x: a * start.x + b * control1.x + c * control2.x + d * end.x, y: a * start.y + b * control1.y + c * control2.y + d * end.y }
+            },
+            _ => unreachable!(),
+        }
+    }
+    #[test]
+    fn flatten() {
+        let coarse = flatten_bezier(&circle(10.0), 0.1).unwrap();
+        let fine = flatten_bezier(&circle(10.0), 0.001).unwrap();
+        assert!(fine.0.len() > 2 * coarse.0.len(), "Smaller tolerance should result in more points");
+        for (ring, tolerance) in [(coarse, 0.1), (fine, 0.001)] {
+            // every point of the curves has to be close to the polyline
+            assert!(circle(10.0).iter().all(|segment| (0..=200).all(|step| {
+                let p = point_at(segment, step as f64 / 200.0);
+                ring.0.windows(2).map(|pair| segment_distance(p, pair[0], pair[1])).fold(f64::MAX, f64::min) <= tolerance
+            })), "Flattening exceeds the tolerance");
+        }
+        let quadratic = flatten_bezier(&[
+            BezierSegment::Quadratic { start: Coordinate { x: 0.0, y: 0.0 }, control: Coordinate { x: 5.0, y: 0.0 }, end: Coordinate { x: 5.0, y: 5.0 } },
+            BezierSegment::Line { start: Coordinate { x: 5.0, y: 5.0 }, end: Coordinate { x: 0.0, y: 0.0 } },
+        ], 0.01).unwrap();
+        assert!(quadratic.0.len() > 4 && quadratic.0.first() == quadratic.0.last(), "Quadratic curve has to be flattened into a closed ring");
+    }
+    #[test]
+    fn offset() {
+        let result = offset_bezier(&[circle(10.0)], 2.0, 0.01).unwrap();
+        assert!(result.0.len() == 1 && result.0[0].interiors().is_empty(), "Offset circle should be one polygon");
+        assert!(result.0[0].exterior().0.iter().all(|p| (p.x.hypot(p.y) - 12.0).abs() <= 0.01 + 10.0 * CIRCLE_ERROR), "Offset circle exceeds the tolerance");
+
+        // a ring with a clockwise inner contour, like the glyph 'o'
+        let mut inner = circle(5.0);
+        inner.reverse();
+        let inner: Vec<_> = inner.into_iter().map(|segment| match segment {
+            BezierSegment::Cubic { start, control1, control2, end } => BezierSegment::Cubic { start: end, control1: control2, control2: control1, end: start },
+            other => other,
+        }).collect();
+        let result = offset_bezier(&[circle(10.0), inner], -1.0, 0.01).unwrap();
+        assert!(result.0.len() == 1 && result.0[0].interiors().len() == 1, "Shrunk ring should keep its hole");
+        assert!(result.0[0].interiors()[0].0.iter().all(|p| (p.x.hypot(p.y) - 6.0).abs() <= 0.01 + 5.0 * CIRCLE_ERROR), "Hole has to grow by the offset");
+    }
+    #[test]
+    fn invalid_tolerance() {
+        assert!(matches!(flatten_bezier(&circle(10.0), 0.0), Err(OffsetError::InvalidArgument(_))), "Zero tolerance has to be rejected");
+        assert!(matches!(offset_bezier(&[circle(10.0)], 1.0, -0.1), Err(OffsetError::InvalidArgument(_))), "Negative tolerance has to be rejected");
+        let mut curves = circle(10.0);
+        curves[1] = BezierSegment::Cubic { start: Coordinate { x: 0.0, y: 10.0 }, control1: Coordinate { x: f64::NAN, y: 10.0 }, control2: Coordinate { x: -10.0, y: 5.0 }, end: Coordinate { x: -10.0, y: 0.0 } };
+        assert!(matches!(flatten_bezier(&curves, 0.01), Err(OffsetError::InvalidArgument(_))), "Coordinates that are not finite have to be reported");
+        assert!(matches!(flatten_bezier(&circle(10.0), 1e-16), Err(OffsetError::InvalidArgument(_))), "Tolerances below the precision have to be reported");
+    }
+}
@@ -0,0 +1,168 @@
+use geo_types::{LineString, Coordinate};
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::ops::{AddAssign, SubAssign};
+
+use super::{Normal, Segment, segment_normal, join, arc, extract_filled_regions, FillRule, OffsetError};
+
+/// Offsets the segment to the side of `normal`, so that it touches the circles with the radii `w0` around `p0` and `w1` around `p1`.
+/// The normal stored in the segment is the normal of that tangent, which differs from `normal` when the widths differ.
+fn tangent_segment<N>(p0: Coordinate<N>, p1: Coordinate<N>, w0: N, w1: N, normal: Normal<N>, len: N, edge: usize) -> Segment<N>
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let sin = ((w0 - w1) / len).max(-N::one()).min(N::one());
+    let cos = (N::one() - sin * sin).sqrt();
+    let tangent_normal = Normal {
+        x: normal.x * cos - normal.y * sin,
+        y: normal.y * cos + normal.x * sin,
+    };
+    Segment {
+        p0: Coordinate {
+            x: p0.x + w0 * tangent_normal.x,
+            y: p0.y + w0 * tangent_normal.y,
+        },
+        p1: Coordinate {
+            x: p1.x + w1 * tangent_normal.x,
+            y: p1.y + w1 * tangent_normal.y,
+        },
+        p1_orig: p1,
+        normal: tangent_normal,
+        offset: w1,
+        edge,
+    }
+}
+
+/// Builds the raw outline of a stroke along the line string, with rounded caps at both ends. Returns an empty curve if all
+/// segments are degenerate.
+fn stroke<N>(line: &[Coordinate<N>], widths: &[N], arcstep: N) -> Vec<Coordinate<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    // walk along the right side of the line and then back along its left side
+    let mut forward = Vec::new();
+    let mut backward = Vec::new();
+    for idx in 1..line.len() {
+        let (p0, p1) = (line[idx-1], line[idx]);
+        let (w0, w1) = (widths[idx-1], widths[idx]);
+        if let Some((normal, len)) = segment_normal(p0, p1) {
+            forward.push(tangent_segment(p0, p1, w0, w1, normal, len, idx-1));
+            backward.push(tangent_segment(p1, p0, w1, w0, Normal { x: -normal.x, y: -normal.y }, len, idx-1));
+        }
+    }
+    if forward.is_empty() {
+        return Vec::new();
+    }
+    let cap = forward.len() - 1;
+    backward.reverse();
+    let lines: Vec<Segment<N>> = forward.into_iter().chain(backward).collect();
+
+    let mut connected = Vec::new();
+    for idx in 0..lines.len() {
+        let (line0, line1) = (&lines[idx], &lines[(idx+1) % lines.len()]);
+        connected.extend_from_slice(&[line0.p0, line0.p1]);
+        if line0.offset == N::zero() { // the stroke ends in a point here
+            connected.pop();
+        } else if idx == cap || idx == lines.len() - 1 {
+            arc(&mut connected, line0.p1_orig, line0.offset, line0.normal.y.atan2(line0.normal.x), line1.normal.y.atan2(line1.normal.x), arcstep);
+        } else {
+            join(&mut connected, line0, line1, line0.offset, arcstep);
+        }
+    }
+    connected.push(connected[0]);
+    connected
+}
+
+/// Returns a closed counter-clockwise circle around `center`.
+fn circle<N>(center: Coordinate<N>, radius: N, arcstep: N) -> Vec<Coordinate<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let start = Coordinate { x: center.x + radius, y: center.y };
+    let mut connected = vec![start];
+    arc(&mut connected, center, radius, N::zero(), N::from_f32(2.0).unwrap() * N::PI(), arcstep);
+    connected.push(start);
+    connected
+}
+
+/// Builds the outline of a stroke along an open line string, where the half-width of the stroke is interpolated linearly between
+/// the widths given for each vertex. The ends of the stroke are rounded.
+///
+/// # Arguments
+///
+/// * `line` - The open line string describing the center of the stroke.
+/// * `widths` - The half-width of the stroke at each coordinate of `line`. It has to contain one entry per coordinate.
+/// * `arcdetail` - Defines how many points should be added in the rounded caps and joins. This number is the number of vertices inserted if it's a full circle.
+///
+/// Returns a vector of polygons. Areas enclosed by the stroke are returned as separate polygons in clockwise order (holes).
+/// Fails with `OffsetError::InvalidArgument` if the number of widths doesn't match the number of coordinates or a width is
+/// negative or not finite, and otherwise with the same error as [`offset_polygon`](fn.offset_polygon.html).
+pub fn variable_width_buffer<N>(line: &LineString<N>, widths: &[N], arcdetail: N) -> Result<Vec<LineString<N>>, OffsetError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    if widths.len() != line.0.len() {
+        return Err(OffsetError::InvalidArgument(format!("expected {} widths, one per coordinate, got {}", line.0.len(), widths.len())));
+    }
+    if let Some(idx) = widths.iter().position(|width| !width.is_finite() || *width < N::zero()) {
+        return Err(OffsetError::InvalidArgument(format!("the width at coordinate {} has to be finite and not negative", idx)));
+    }
+    let arcstep = N::from_f32(2.0).unwrap() * N::PI() / arcdetail;
+
+    // Where the circle around one end of a segment contains the circle around its other end, there is no tangent between them
+    // and the larger circle covers the stroke along the segment. The line is split there, ending both parts with a cap.
+    let mut curves = Vec::new();
+    let mut run = 0;
+    for idx in 1..line.0.len() {
+        let (p0, p1) = (line.0[idx-1], line.0[idx]);
+        let (w0, w1) = (widths[idx-1], widths[idx]);
+        if let Some((_, len)) = segment_normal(p0, p1) {
+            if (w0 - w1).abs() >= len {
+                curves.push(stroke(&line.0[run..idx], &widths[run..idx], arcstep));
+                curves.push(if w0 > w1 { circle(p0, w0, arcstep) } else { circle(p1, w1, arcstep) });
+                run = idx;
+            }
+        }
+    }
+    curves.push(stroke(&line.0[run..], &widths[run..], arcstep));
+
+    Ok(extract_filled_regions(curves, FillRule::Positive, None)?.into_iter().map(|(region, _)| region).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn constant_width() {
+        let input = LineString(vec![Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 10.0, y: 0.0 }]);
+        let result = variable_width_buffer(&input, &[1.0, 1.0], 4.0).unwrap();
+        assert!(result.len() == 1, "Buffering a single segment should result in one polygon");
+        let first = result.first().unwrap();
+        assert!(first.0.iter().zip(vec![
+            Coordinate { x: 0.0, y: -1.0 }, Coordinate { x: 10.0, y: -1.0 }, Coordinate { x: 11.0, y: 0.0 }, Coordinate { x: 10.0, y: 1.0 }, Coordinate { x: 0.0, y: 1.0 }, Coordinate { x: -1.0, y: 0.0 }, Coordinate { x: 0.0, y: -1.0 },
+        ]).all(|(p0, p1)| (p0.x - p1.x).abs() < 1e-12 && (p0.y - p1.y).abs() < 1e-12), "Incorrect buffer for a single segment");
+    }
+    #[test]
+    fn tapered() {
+        let input = LineString(vec![Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 10.0, y: 0.0 }, Coordinate { x: 10.0, y: 10.0 }]);
+        let result = variable_width_buffer(&input, &[1.0, 2.0, 0.5], 20.0).unwrap();
+        assert!(result.len() == 1, "Buffering a tapered line should result in one polygon");
+        let first = result.first().unwrap();
+        // every point of the outline has to be on the circle of one of the vertices or on a tangent between two of them
+        assert!(first.0.iter().all(|p| {
+            let distance = |x: f64, y: f64| ((p.x - x) * (p.x - x) + (p.y - y) * (p.y - y)).sqrt();
+            distance(0.0, 0.0) > 1.0 - 1e-9 && distance(10.0, 0.0) > 2.0 - 1e-9 && distance(10.0, 10.0) > 0.5 - 1e-9
+        }), "Tapered buffer outline cuts into the stroke");
+        let max_x = first.0.iter().map(|p| p.x).fold(f64::MIN, f64::max);
+        assert!(max_x > 11.9 && max_x < 12.0 + 1e-9, "Tapered buffer should be widest at the middle vertex");
+    }
+    #[test]
+    fn nested_circles() {
+        let input = LineString(vec![Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 1.0, y: 0.0 }, Coordinate { x: 10.0, y: 0.0 }]);
+        let result = variable_width_buffer(&input, &[3.0, 0.5, 0.5], 40.0).unwrap();
+        assert!(result.len() == 1, "A wide end containing the next vertex should result in one polygon");
+        let min_x = result[0].0.iter().map(|p| p.x).fold(f64::MAX, f64::min);
+        let max_y = result[0].0.iter().map(|p| p.y).fold(f64::MIN, f64::max);
+        assert!((min_x + 3.0).abs() < 1e-9 && (max_y - 3.0).abs() < 1e-9, "The larger circle has to cover the segment");
+        assert!(result[0].0.iter().all(|p| p.x.hypot(p.y) > 3.0 - 1e-9 || p.y.abs() > 0.5 - 1e-9), "The outline must not cut into the circle");
+    }
+    #[test]
+    fn invalid_widths() {
+        let input = LineString(vec![Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 10.0, y: 0.0 }]);
+        assert!(matches!(variable_width_buffer(&input, &[1.0], 4.0), Err(OffsetError::InvalidArgument(_))), "Missing widths have to be reported");
+        assert!(matches!(variable_width_buffer(&input, &[1.0, -1.0], 4.0), Err(OffsetError::InvalidArgument(_))), "Negative widths have to be reported");
+        assert!(matches!(variable_width_buffer(&input, &[f64::NAN, 1.0], 4.0), Err(OffsetError::InvalidArgument(_))), "Widths that are not a number have to be reported");
+        assert!(matches!(variable_width_buffer(&input, &[1.0, f64::INFINITY], 4.0), Err(OffsetError::InvalidArgument(_))), "Infinite widths have to be reported");
+    }
+}
@@ -0,0 +1,125 @@
+use geo_types::Coordinate;
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::ops::{AddAssign, SubAssign};
+
+use super::{PathSegment, Path, offset_path, CombinatorialExplosionError};
+
+/// A vertex of a polyline with the bulge of the segment that starts at it, as used by DXF's LWPOLYLINE. The bulge is the tangent
+/// of a quarter of the arc's sweep angle, positive for counter-clockwise arcs and `0` for straight lines.
+pub type BulgeVertex<N> = (Coordinate<N>, N);
+
+/// Converts a closed polyline with bulges into a path of lines and arcs. The last vertex is connected back to the first one;
+/// it may also repeat the first vertex.
+pub fn path_from_bulges<N>(vertices: &[BulgeVertex<N>]) -> Path<N>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    let count = match (vertices.first(), vertices.last()) {
+        (Some(first), Some(last)) if vertices.len() > 1 && first.0 == last.0 => vertices.len() - 1,
+        _ => vertices.len(),
+    };
+    (0..count).filter_map(|idx| {
+        let ((start, bulge), (end, _)) = (vertices[idx], vertices[(idx+1) % count]);
+        if start == end {
+            return None;
+        }
+        if bulge == N::zero() {
+            return Some(PathSegment::Line { start, end });
+        }
+        let sweep = N::from_f32(4.0).unwrap() * bulge.atan();
+        let half = sweep / N::from_f32(2.0).unwrap();
+        let (dx, dy) = (end.x - start.x, end.y - start.y);
+        let length = dx.hypot(dy);
+        // the center is on the perpendicular bisector of the chord
+        let along = length / N::from_f32(2.0).unwrap() / half.tan();
+        let center = Coordinate {
+            x: (start.x + end.x) / N::from_f32(2.0).unwrap() - dy / length * along,
+            y: (start.y + end.y) / N::from_f32(2.0).unwrap() + dx / length * along,
+        };
+        let start_angle = (start.y - center.y).atan2(start.x - center.x);
+        Some(PathSegment::Arc {
+            center,
+            radius: (length / N::from_f32(2.0).unwrap() / half.sin()).abs(),
+            start_angle,
+            end_angle: start_angle + sweep,
+        })
+    }).collect()
+}
+
+/// Converts a closed path into a polyline with bulges, the inverse of [`path_from_bulges`](fn.path_from_bulges.html).
+/// The first vertex is not repeated at the end.
+pub fn bulges_from_path<N>(path: &[PathSegment<N>]) -> Vec<BulgeVertex<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    path.iter().map(|segment| match *segment {
+        PathSegment::Line { start, .. } => (start, N::zero()),
+        PathSegment::Arc { start_angle, end_angle, .. } => (segment.start(), ((end_angle - start_angle) / N::from_f32(4.0).unwrap()).tan()),
+    }).collect()
+}
+
+/// Expands or shrinks a closed polyline with bulges, like the rings of LWPOLYLINE entities. Arcs are offset concentrically
+/// instead of being approximated, see [`offset_path`](fn.offset_path.html).
+///
+/// # Arguments
+///
+/// * `vertices` - The vertices of a closed counter-clockwise polyline, each with the bulge of the segment starting at it.
+/// * `offset` - A positive number expands the polyline, a negative number shrinks it.
+///
+/// Returns a vector of closed polylines with bulges. Polylines around areas are counter-clockwise, holes are clockwise. The
+/// error is the same as the one of [`offset_polygon`](fn.offset_polygon.html).
+pub fn offset_bulge_polyline<N>(vertices: &[BulgeVertex<N>], offset: N) -> Result<Vec<Vec<BulgeVertex<N>>>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    Ok(offset_path(&path_from_bulges(vertices), offset)?.iter().map(|path| bulges_from_path(path)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn stadium(radius: f64) -> Vec<BulgeVertex<f64>> {
+        vec![
+            (Coordinate { x: 0.0, y: 1.0 - radius }, 0.0), (Coordinate { x: 10.0, y: 1.0 - radius }, 1.0),
+            (Coordinate { x: 10.0, y: 1.0 + radius }, 0.0), (Coordinate { x: 0.0, y: 1.0 + radius }, 1.0),
+        ]
+    }
+    fn close(a: &[BulgeVertex<f64>], b: &[BulgeVertex<f64>]) -> bool {
+        // the output may start at any vertex
+        a.len() == b.len() && (0..b.len()).any(|shift| a.iter().enumerate().all(|(idx, (p, bulge))| {
+            let (q, other) = b[(idx + shift) % b.len()];
+            (p.x - q.x).abs() < 1e-9 && (p.y - q.y).abs() < 1e-9 && (bulge - other).abs() < 1e-9
+        }))
+    }
+    #[test]
+    fn conversion() {
+        let path = path_from_bulges(&stadium(1.0));
+        assert!(path.len() == 4, "Stadium consists of two lines and two arcs");
+        match path[1] {
+            PathSegment::Arc { center, radius, start_angle, end_angle } => assert!((center.x - 10.0).abs() < 1e-12 && (center.y - 1.0).abs() < 1e-12 && (radius - 1.0).abs() < 1e-12 && (end_angle - start_angle - std::f64::consts::PI).abs() < 1e-12, "Incorrect arc for bulge 1"),
+            _ => panic!("Bulge has to result in an arc"),
+        }
+        assert!(close(&bulges_from_path(&path), &stadium(1.0)), "Conversion back to bulges has to result in the same polyline");
+
+        let quarter = path_from_bulges(&[(Coordinate { x: 1.0, y: 0.0 }, -(std::f64::consts::PI / 8.0).tan()), (Coordinate { x: 0.0, y: -1.0 }, 0.0)]);
+        match quarter[0] {
+            PathSegment::Arc { center, radius, start_angle, end_angle } => assert!(center.x.abs() < 1e-12 && center.y.abs() < 1e-12 && (radius - 1.0).abs() < 1e-12 && (end_angle - start_angle + std::f64::consts::FRAC_PI_2).abs() < 1e-12, "Incorrect clockwise arc"),
+            _ => panic!("Bulge has to result in an arc"),
+        }
+    }
+    #[test]
+    fn offset() {
+        let result = offset_bulge_polyline(&stadium(1.0), 1.0).unwrap();
+        assert!(result.len() == 1 && close(&result[0], &stadium(2.0)), "Expanded stadium has to have concentric arcs");
+        let result = offset_bulge_polyline(&stadium(1.0), -0.5).unwrap();
+        assert!(result.len() == 1 && close(&result[0], &stadium(0.5)), "Shrunk stadium has to have concentric arcs");
+        assert!(offset_bulge_polyline(&stadium(1.0), -1.5).unwrap().is_empty(), "Stadium has to vanish when shrunk by more than its radius");
+
+        // a square with one rounded corner cut out of it: shrinking it turns the concave arc into a larger concave arc
+        let notched = vec![
+            (Coordinate { x: 0.0, y: 0.0 }, 0.0), (Coordinate { x: 10.0, y: 0.0 }, 0.0), (Coordinate { x: 10.0, y: 8.0 }, -(std::f64::consts::PI / 8.0).tan()),
+            (Coordinate { x: 8.0, y: 10.0 }, 0.0), (Coordinate { x: 0.0, y: 10.0 }, 0.0),
+        ];
+        let result = offset_bulge_polyline(&notched, -1.0).unwrap();
+        assert!(result.len() == 1 && result[0].len() == 5, "Shrunk notched square has four lines and one arc");
+        let arc = path_from_bulges(&result[0]).into_iter().find(|segment| matches!(segment, PathSegment::Arc { .. })).unwrap();
+        match arc {
+            PathSegment::Arc { center, radius, .. } => assert!((center.x - 10.0).abs() < 1e-12 && (center.y - 10.0).abs() < 1e-12 && (radius - 3.0).abs() < 1e-12, "Concave arc has to grow by the offset"),
+            _ => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,559 @@
+use geo_types::{LineString, Coordinate};
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::ops::{AddAssign, SubAssign};
+
+use super::{segment_normal, FillRule, CombinatorialExplosionError, MAX_INTERSECTIONS, intersect::{sub, cross_product, dot_product}};
+
+/// A segment of a path, either a straight line or a circular arc.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathSegment<N: Num + Copy + NumCast + PartialOrd> {
+    Line {
+        start: Coordinate<N>,
+        end: Coordinate<N>,
+    },
+    /// An arc around `center`. The angles are in radians, `end_angle` is larger than `start_angle` for counter-clockwise arcs
+    /// and smaller for clockwise arcs.
+    Arc {
+        center: Coordinate<N>,
+        radius: N,
+        start_angle: N,
+        end_angle: N,
+    },
+}
+
+/// A closed path made of lines and arcs, where every segment starts at the end of the one before it.
+pub type Path<N> = Vec<PathSegment<N>>;
+
+fn distance<N>(a: Coordinate<N>, b: Coordinate<N>) -> N
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+/// Returns the angle in `[0, 2π)`.
+fn normalize_angle<N>(angle: N) -> N
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    let full = N::from_f32(2.0).unwrap() * N::PI();
+    let angle = angle % full;
+    if angle.is_sign_negative() { angle + full } else { angle }
+}
+
+impl<N> PathSegment<N> where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    /// Returns the point at position `t` along the segment (`0` at its start, `1` at its end).
+    pub fn point_at(&self, t: N) -> Coordinate<N> {
+        match *self {
+            PathSegment::Line { start, end } => Coordinate {
+                x: start.x + t * (end.x - start.x),
+                y: start.y + t * (end.y - start.y),
+            },
+            PathSegment::Arc { center, radius, start_angle, end_angle } => {
+                let angle = start_angle + t * (end_angle - start_angle);
+                Coordinate {
+                    x: center.x + radius * angle.cos(),
+                    y: center.y + radius * angle.sin(),
+                }
+            },
+        }
+    }
+
+    pub fn start(&self) -> Coordinate<N> {
+        match *self {
+            PathSegment::Line { start, .. } => start,
+            _ => self.point_at(N::zero()),
+        }
+    }
+
+    pub fn end(&self) -> Coordinate<N> {
+        match *self {
+            PathSegment::Line { end, .. } => end,
+            _ => self.point_at(N::one()),
+        }
+    }
+
+    /// Returns the direction of the segment at position `t` (not normalized).
+    pub fn tangent_at(&self, t: N) -> Coordinate<N> {
+        match *self {
+            PathSegment::Line { start, end } => sub(end, start),
+            PathSegment::Arc { radius, start_angle, end_angle, .. } => {
+                let angle = start_angle + t * (end_angle - start_angle);
+                let sweep = end_angle - start_angle;
+                Coordinate {
+                    x: -radius * sweep * angle.sin(),
+                    y: radius * sweep * angle.cos(),
+                }
+            },
+        }
+    }
+
+    /// Returns the part of the segment between the positions `t0` and `t1`.
+    pub fn part(&self, t0: N, t1: N) -> PathSegment<N> {
+        match *self {
+            PathSegment::Line { .. } => PathSegment::Line {
+                start: self.point_at(t0),
+                end: self.point_at(t1),
+            },
+            PathSegment::Arc { center, radius, start_angle, end_angle } => PathSegment::Arc {
+                center,
+                radius,
+                start_angle: start_angle + t0 * (end_angle - start_angle),
+                end_angle: start_angle + t1 * (end_angle - start_angle),
+            },
+        }
+    }
+
+    /// Returns the same segment running in the other direction.
+    pub fn reversed(&self) -> PathSegment<N> {
+        match *self {
+            PathSegment::Line { start, end } => PathSegment::Line { start: end, end: start },
+            PathSegment::Arc { center, radius, start_angle, end_angle } => PathSegment::Arc {
+                center,
+                radius,
+                start_angle: end_angle,
+                end_angle: start_angle,
+            },
+        }
+    }
+
+    /// Returns the position along an arc of the point with the given angle around its center, which is outside of `[0, 1]` if the
+    /// angle is not covered by the arc.
+    fn arc_position(start_angle: N, end_angle: N, angle: N) -> N {
+        let sweep = end_angle - start_angle;
+        let full = N::from_f32(2.0).unwrap() * N::PI();
+        // measure in the direction of the arc, and map the part that's not covered symmetrically around the arc
+        let offset = if sweep >= N::zero() { normalize_angle(angle - start_angle) } else { normalize_angle(start_angle - angle) };
+        let offset = if offset > (sweep.abs() + full) * N::from_f32(0.5).unwrap() { offset - full } else { offset };
+        offset / sweep.abs()
+    }
+
+    /// Adds the points of the segment to `points`, except for its start point. Arcs are approximated by one point every `arcstep` radians.
+    pub fn flatten(&self, points: &mut Vec<Coordinate<N>>, arcstep: N) {
+        if let PathSegment::Arc { start_angle, end_angle, .. } = *self {
+            let steps = <usize as NumCast>::from(((end_angle - start_angle).abs() / arcstep).ceil()).unwrap_or(1).max(1);
+            for step in 1..steps {
+                points.push(self.point_at(N::from(step).unwrap() / N::from(steps).unwrap()));
+            }
+        }
+        points.push(self.end());
+    }
+}
+
+/// Returns the signed area of a closed path, positive for counter-clockwise paths. Arcs add or remove the circular segment
+/// between their chord and the arc.
+pub fn path_area<N>(path: &[PathSegment<N>]) -> N
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    let half = N::from_f32(0.5).unwrap();
+    path.iter().fold(N::zero(), |area, segment| {
+        let (start, end) = (segment.start(), segment.end());
+        let chord = (start.x * end.y - end.x * start.y) * half;
+        match *segment {
+            PathSegment::Arc { radius, start_angle, end_angle, .. } => {
+                let sweep = end_angle - start_angle;
+                area + chord + radius * radius * (sweep - sweep.sin()) * half
+            },
+            _ => area + chord,
+        }
+    })
+}
+
+/// Converts the path into a closed line string, approximating arcs with `arcdetail` points per full circle.
+pub fn flatten_path<N>(path: &[PathSegment<N>], arcdetail: N) -> LineString<N>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    let arcstep = N::from_f32(2.0).unwrap() * N::PI() / arcdetail;
+    let mut points = Vec::new();
+    if let Some(first) = path.first() {
+        points.push(first.start());
+    }
+    for segment in path {
+        segment.flatten(&mut points, arcstep);
+    }
+    LineString(points)
+}
+
+/// Returns the positions along both segments where they intersect. Intersections are calculated exactly (up to floating point
+/// precision) for all combinations of lines and arcs.
+fn intersections<N>(a: &PathSegment<N>, b: &PathSegment<N>, tolerance: N) -> Vec<(N, N)>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    let within = |t: N, length: N| t >= -tolerance / length && t <= N::one() + tolerance / length;
+    let length = |segment: &PathSegment<N>| match *segment {
+        PathSegment::Line { start, end } => distance(start, end),
+        PathSegment::Arc { radius, start_angle, end_angle, .. } => radius * (end_angle - start_angle).abs(),
+    };
+    let (length_a, length_b) = (length(a), length(b));
+    if length_a <= tolerance || length_b <= tolerance {
+        return Vec::new();
+    }
+    let candidates = match (*a, *b) {
+        (PathSegment::Line { start: p, end: p_end }, PathSegment::Line { start: q, end: q_end }) => {
+            let (r, s) = (sub(p_end, p), sub(q_end, q));
+            let denominator = cross_product(r, s);
+            if denominator.abs() <= N::epsilon() * length_a * length_b {
+                return Vec::new();
+            }
+            let q_p = sub(q, p);
+            vec![(cross_product(q_p, s) / denominator, cross_product(q_p, r) / denominator)]
+        },
+        (PathSegment::Line { .. }, PathSegment::Arc { .. }) => {
+            return intersections(b, a, tolerance).into_iter().map(|(t, u)| (u, t)).collect();
+        },
+        (PathSegment::Arc { center, radius, start_angle, end_angle }, PathSegment::Line { start, end }) => {
+            // points on the line are start + u * d, solve |start + u * d - center| = radius
+            let (d, f) = (sub(end, start), sub(start, center));
+            let (qa, qb, qc) = (dot_product(d, d), N::from_f32(2.0).unwrap() * dot_product(f, d), dot_product(f, f) - radius * radius);
+            let discriminant = qb * qb - N::from_f32(4.0).unwrap() * qa * qc;
+            // tangents are accepted within the tolerance
+            let slack = N::from_f32(4.0).unwrap() * qa * tolerance * (radius + radius);
+            if discriminant < -slack {
+                return Vec::new();
+            }
+            let root = discriminant.max(N::zero()).sqrt();
+            let roots = if root == N::zero() { vec![-qb / (qa + qa)] } else { vec![(-qb - root) / (qa + qa), (-qb + root) / (qa + qa)] };
+            roots.into_iter().map(|u| {
+                let p = Coordinate { x: start.x + u * d.x, y: start.y + u * d.y };
+                let angle = (p.y - center.y).atan2(p.x - center.x);
+                (PathSegment::arc_position(start_angle, end_angle, angle), u)
+            }).collect()
+        },
+        (PathSegment::Arc { center: c0, radius: r0, start_angle: s0, end_angle: e0 }, PathSegment::Arc { center: c1, radius: r1, start_angle: s1, end_angle: e1 }) => {
+            let d = distance(c0, c1);
+            if d <= tolerance || d > r0 + r1 + tolerance || d < (r0 - r1).abs() - tolerance {
+                return Vec::new(); // concentric, separate or nested circles
+            }
+            let along = (d * d + r0 * r0 - r1 * r1) / (d + d);
+            let h = (r0 * r0 - along * along).max(N::zero()).sqrt();
+            let direction = Coordinate { x: (c1.x - c0.x) / d, y: (c1.y - c0.y) / d };
+            let base = Coordinate { x: c0.x + along * direction.x, y: c0.y + along * direction.y };
+            let points = if h == N::zero() {
+                vec![base]
+            } else {
+                vec![Coordinate { x: base.x - h * direction.y, y: base.y + h * direction.x }, Coordinate { x: base.x + h * direction.y, y: base.y - h * direction.x }]
+            };
+            points.into_iter().map(|p| {
+                (PathSegment::arc_position(s0, e0, (p.y - c0.y).atan2(p.x - c0.x)), PathSegment::arc_position(s1, e1, (p.y - c1.y).atan2(p.x - c1.x)))
+            }).collect()
+        },
+    };
+    candidates.into_iter().filter(|(t, u)| within(*t, length_a) && within(*u, length_b))
+        .map(|(t, u)| (t.max(N::zero()).min(N::one()), u.max(N::zero()).min(N::one()))).collect()
+}
+
+/// Returns how often the closed path winds around the point. Each arc contributes the angle of its chord, plus a full turn if
+/// the point is between the chord and the arc.
+pub(crate) fn winding_number<N>(path: &[PathSegment<N>], point: Coordinate<N>) -> isize
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    let full = N::from_f32(2.0).unwrap() * N::PI();
+    let total = path.iter().fold(N::zero(), |total, segment| {
+        let (a, b) = (sub(segment.start(), point), sub(segment.end(), point));
+        let mut angle = cross_product(a, b).atan2(dot_product(a, b));
+        if let PathSegment::Arc { center, radius, start_angle, end_angle } = *segment {
+            let (start, end, middle) = (segment.start(), segment.end(), segment.point_at(N::from_f32(0.5).unwrap()));
+            let chord = sub(end, start);
+            let side = cross_product(chord, sub(point, start));
+            let arc_side = cross_product(chord, sub(middle, start));
+            if distance(point, center) < radius && side * arc_side > N::zero() {
+                angle = if end_angle > start_angle { angle + full } else { angle - full };
+            }
+        }
+        total + angle
+    });
+    <isize as NumCast>::from((total / full).round()).unwrap_or(0)
+}
+
+/// Resolves the self-intersections of a raw offset curve made of lines and arcs. The curve is split at all intersections and the
+/// pieces that separate areas with a positive winding number from the rest are linked into closed paths, using the same fill
+/// rule as the regions of `offset_polygon`.
+fn resolve<N>(raw: &[PathSegment<N>]) -> Result<Vec<Path<N>>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    let extent = raw.iter().fold(N::one(), |extent, segment| {
+        let (start, end) = (segment.start(), segment.end());
+        extent.max(start.x.abs()).max(start.y.abs()).max(end.x.abs()).max(end.y.abs())
+    });
+    let tolerance = extent * N::epsilon().powf(N::from_f32(0.75).unwrap());
+    let probe = extent * N::epsilon().sqrt();
+
+    let mut splits: Vec<Vec<N>> = vec![vec![N::zero(), N::one()]; raw.len()];
+    let mut count = 0;
+    for i in 0..raw.len() {
+        for j in (i+1)..raw.len() {
+            for (t, u) in intersections(&raw[i], &raw[j], tolerance) {
+                count += 1;
+                if count > MAX_INTERSECTIONS {
+                    return Err(CombinatorialExplosionError);
+                }
+                splits[i].push(t);
+                splits[j].push(u);
+            }
+        }
+    }
+    let mut pieces = Vec::new();
+    for (segment, mut split) in raw.iter().zip(splits) {
+        split.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for pair in split.windows(2) {
+            let piece = segment.part(pair[0], pair[1]);
+            if distance(piece.start(), piece.end()) > tolerance || (pair[1] - pair[0]) > N::from_f32(0.5).unwrap() {
+                pieces.push(piece);
+            }
+        }
+    }
+
+    // keep the pieces between filled and empty areas, oriented so that the filled area is on their left
+    let kept: Vec<PathSegment<N>> = pieces.into_iter().filter_map(|piece| {
+        let (middle, tangent) = (piece.point_at(N::from_f32(0.5).unwrap()), piece.tangent_at(N::from_f32(0.5).unwrap()));
+        let length = tangent.x.hypot(tangent.y);
+        if length == N::zero() {
+            return None;
+        }
+        let normal = Coordinate { x: -tangent.y / length * probe, y: tangent.x / length * probe };
+        let left = winding_number(raw, Coordinate { x: middle.x + normal.x, y: middle.y + normal.y });
+        let right = winding_number(raw, Coordinate { x: middle.x - normal.x, y: middle.y - normal.y });
+        FillRule::Positive.filled_side(left, right).map(|filled| if filled == left { piece } else { piece.reversed() })
+    }).collect();
+
+    Ok(link(kept, probe))
+}
+
+/// Links the pieces into closed paths. Where several pieces start at the end of a piece, the path continues with the first one
+/// clockwise from the direction it came from.
+fn link<N>(pieces: Vec<PathSegment<N>>, tolerance: N) -> Vec<Path<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    let angle = |d: Coordinate<N>| d.y.atan2(d.x);
+    let full = N::from_f32(2.0).unwrap() * N::PI();
+    let mut used = vec![false; pieces.len()];
+    let mut paths = Vec::new();
+    for first in 0..pieces.len() {
+        if used[first] {
+            continue;
+        }
+        let mut path = Vec::new();
+        let mut current = first;
+        let closed = loop {
+            used[current] = true;
+            path.push(pieces[current]);
+            let end = pieces[current].end();
+            if distance(end, pieces[first].start()) <= tolerance && path.len() > 1 && !(0..pieces.len()).any(|idx| !used[idx] && distance(pieces[idx].start(), end) <= tolerance) {
+                break true;
+            }
+            let incoming = pieces[current].tangent_at(N::one());
+            let back = angle(Coordinate { x: -incoming.x, y: -incoming.y });
+            let next = (0..pieces.len()).filter(|idx| (!used[*idx] || *idx == first) && distance(pieces[*idx].start(), end) <= tolerance).map(|idx| {
+                let turn = (back - angle(pieces[idx].tangent_at(N::zero()))) % full;
+                (idx, if turn <= N::zero() { turn + full } else { turn })
+            }).min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+            match next {
+                Some((next, _)) if next == first => break true,
+                Some((next, _)) => current = next,
+                None => break false,
+            }
+        };
+        if closed {
+            paths.push(merge(path, tolerance));
+        }
+    }
+    paths
+}
+
+/// Merges consecutive lines that continue each other and consecutive arcs on the same circle, and connects the segments exactly.
+fn merge<N>(path: Vec<PathSegment<N>>, tolerance: N) -> Path<N>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    let mut merged: Vec<PathSegment<N>> = Vec::with_capacity(path.len());
+    for segment in path {
+        let combined = match (merged.last(), segment) {
+            (Some(PathSegment::Line { start, end }), PathSegment::Line { end: next_end, .. }) => {
+                let (d0, d1) = (sub(*end, *start), sub(next_end, *end));
+                if cross_product(d0, d1).abs() <= tolerance * distance(*start, *end) && dot_product(d0, d1) > N::zero() {
+                    Some(PathSegment::Line { start: *start, end: next_end })
+                } else {
+                    None
+                }
+            },
+            (Some(PathSegment::Arc { center, radius, start_angle, end_angle }), PathSegment::Arc { center: next_center, radius: next_radius, start_angle: next_start, end_angle: next_end })
+                    if distance(*center, next_center) <= tolerance && (*radius - next_radius).abs() <= tolerance && (*end_angle > *start_angle) == (next_end > next_start) => {
+                // continue the angles of the first arc
+                let full = N::from_f32(2.0).unwrap() * N::PI();
+                let shift = ((*end_angle - next_start) / full).round() * full;
+                Some(PathSegment::Arc { center: *center, radius: *radius, start_angle: *start_angle, end_angle: next_end + shift })
+            },
+            _ => None,
+        };
+        match combined {
+            Some(combined) => *merged.last_mut().unwrap() = combined,
+            None => merged.push(segment),
+        }
+    }
+    // the first and the last segment might continue each other as well
+    if merged.len() > 2 {
+        let last = merged.pop().unwrap();
+        let first = merged.remove(0);
+        let mut rest = merge(vec![last, first], tolerance);
+        if rest.len() == 1 {
+            merged.insert(0, rest.pop().unwrap());
+        } else {
+            merged.insert(0, first);
+            merged.push(last);
+        }
+    }
+    // lines end exactly where the next segment starts
+    for idx in 0..merged.len() {
+        let next_start = merged[(idx+1) % merged.len()].start();
+        if let PathSegment::Line { ref mut end, .. } = merged[idx] {
+            *end = next_start;
+        }
+        let previous_end = merged[(idx + merged.len() - 1) % merged.len()].end();
+        if let PathSegment::Line { ref mut start, .. } = merged[idx] {
+            *start = previous_end;
+        }
+    }
+    merged
+}
+
+/// Builds the raw offset curve of a closed path: every segment is moved to its right by `offset` (lines in parallel, arcs
+/// concentrically), and consecutive segments are joined by an arc around their common vertex or via the vertex itself.
+fn raw_offset<N>(path: &[PathSegment<N>], offset: N) -> Vec<PathSegment<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    // the normal to the right of the given direction
+    let normal = |d: Coordinate<N>| {
+        let length = d.x.hypot(d.y);
+        Coordinate { x: d.y / length, y: -d.x / length }
+    };
+    let originals: Vec<PathSegment<N>> = path.iter().cloned().filter(|segment| match *segment {
+        PathSegment::Line { start, end } => segment_normal(start, end).is_some(),
+        PathSegment::Arc { radius, start_angle, end_angle, .. } => radius > N::zero() && start_angle != end_angle,
+    }).collect();
+    let offset_segments: Vec<PathSegment<N>> = originals.iter().map(|segment| match *segment {
+        PathSegment::Line { start, end } => {
+            let (n, _) = segment_normal(start, end).unwrap();
+            PathSegment::Line {
+                start: Coordinate { x: start.x + offset * n.x, y: start.y + offset * n.y },
+                end: Coordinate { x: end.x + offset * n.x, y: end.y + offset * n.y },
+            }
+        },
+        PathSegment::Arc { center, radius, start_angle, end_angle } => {
+            // the right side of a counter-clockwise arc is outside of the circle
+            let radius = if end_angle > start_angle { radius + offset } else { radius - offset };
+            if radius >= N::zero() {
+                PathSegment::Arc { center, radius, start_angle, end_angle }
+            } else { // the arc passes through its center and continues on the opposite side
+                PathSegment::Arc { center, radius: -radius, start_angle: start_angle + N::PI(), end_angle: end_angle + N::PI() }
+            }
+        },
+    }).collect();
+
+    let mut raw = Vec::new();
+    for idx in 0..offset_segments.len() {
+        let (segment, next) = (offset_segments[idx], offset_segments[(idx+1) % offset_segments.len()]);
+        let (original, next_original) = (originals[idx], originals[(idx+1) % originals.len()]);
+        raw.push(segment);
+        let (end, start) = (segment.end(), next.start());
+        if distance(end, start) <= N::epsilon() * (N::one() + end.x.abs().max(end.y.abs())) {
+            continue;
+        }
+        let vertex = original.end();
+        let (t0, t1) = (original.tangent_at(N::one()), next_original.tangent_at(N::zero()));
+        let turn = cross_product(t0, t1);
+        let reverse = dot_product(t0, t1) < N::zero() && turn.abs() <= N::epsilon() * t0.x.hypot(t0.y) * t1.x.hypot(t1.y);
+        if offset != N::zero() && (turn * offset > N::zero() || reverse) {
+            // the offset moves away from the corner, so it's rounded with an arc around the vertex
+            let (n0, n1) = (normal(t0), normal(t1));
+            let (n0, n1) = if offset > N::zero() { (n0, n1) } else { (Coordinate { x: -n0.x, y: -n0.y }, Coordinate { x: -n1.x, y: -n1.y }) };
+            let start_angle = n0.y.atan2(n0.x);
+            let sweep = normalize_angle(n1.y.atan2(n1.x) - start_angle);
+            let end_angle = if offset > N::zero() { start_angle + sweep } else { start_angle - (N::from_f32(2.0).unwrap() * N::PI() - sweep) };
+            raw.push(PathSegment::Arc { center: vertex, radius: offset.abs(), start_angle, end_angle });
+        } else {
+            raw.push(PathSegment::Line { start: end, end: vertex });
+            raw.push(PathSegment::Line { start: vertex, end: start });
+        }
+    }
+    raw
+}
+
+/// Expands or shrinks a closed path made of lines and circular arcs. Lines are moved in parallel and arcs concentrically, so the
+/// result is exact and doesn't depend on how finely the arcs would have been approximated. All intersections are calculated
+/// analytically.
+///
+/// # Arguments
+///
+/// * `path` - A closed counter-clockwise path to shrink or expand. Every segment has to start where the previous one ends.
+/// * `offset` - A positive number expands the path, a negative number shrinks it.
+///
+/// Returns a vector of closed paths. Paths around areas are counter-clockwise, holes are clockwise. The error is the same as the
+/// one of [`offset_polygon`](fn.offset_polygon.html).
+pub fn offset_path<N>(path: &[PathSegment<N>], offset: N) -> Result<Vec<Path<N>>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let raw = raw_offset(path, offset);
+    if raw.is_empty() {
+        return Ok(Vec::new());
+    }
+    resolve(&raw)
+}
+
+/// Like [`offset_path`](fn.offset_path.html), but offsets several closed paths together, like a polygon with holes. Holes have
+/// to be clockwise, so that a positive offset shrinks them. The offsets of all paths are resolved against each other.
+pub fn offset_paths<N>(paths: &[Path<N>], offset: N) -> Result<Vec<Path<N>>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let raw: Vec<PathSegment<N>> = paths.iter().flat_map(|path| raw_offset(path, offset)).collect();
+    if raw.is_empty() {
+        return Ok(Vec::new());
+    }
+    resolve(&raw)
+}
+
+/// Like [`offset_polygon`](fn.offset_polygon.html), but returns the result as paths made of lines and true circular arcs instead
+/// of approximating the arcs in the corners with points. All intersections are calculated exactly between lines and arcs.
+///
+/// # Arguments
+///
+/// * `polygon` - A polygon to shrink or expand. It has to be closed (the last coordinate has to be the same as the first coordinate)
+/// * `offset` - A positive number expands the polygon, a negative number shrinks it.
+///
+/// Returns a vector of closed paths. Paths around areas are counter-clockwise, holes are clockwise.
+/// [`flatten_path`](fn.flatten_path.html) converts them back to line strings. The error is the same as the one of `offset_polygon`.
+pub fn offset_polygon_arcs<N>(polygon: &LineString<N>, offset: N) -> Result<Vec<Path<N>>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let path: Path<N> = polygon.0.windows(2).map(|points| PathSegment::Line { start: points[0], end: points[1] }).collect();
+    offset_path(&path, offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn area(path: &[PathSegment<f64>]) -> f64 {
+        // exact area: the polygon of the end points plus the circular segments of the arcs
+        path.iter().map(|segment| {
+            let (start, end) = (segment.start(), segment.end());
+            let chord = (start.x * end.y - end.x * start.y) / 2.0;
+            match *segment {
+                PathSegment::Arc { radius, start_angle, end_angle, .. } => {
+                    let sweep = end_angle - start_angle;
+                    chord + radius * radius * (sweep - sweep.sin()) / 2.0
+                },
+                _ => chord,
+            }
+        }).sum()
+    }
+    #[test]
+    fn rectangle() {
+        let input: LineString<f64> = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 5.0), (0.0, 5.0), (0.0, 0.0)].into();
+        let result = offset_polygon_arcs(&input, 1.0).unwrap();
+        assert!(result.len() == 1 && result[0].len() == 8, "Expanded rectangle consists of four lines and four arcs");
+        assert!(result[0].iter().filter(|segment| match segment { PathSegment::Arc { radius, .. } => (radius - 1.0).abs() < 1e-12, _ => false }).count() == 4, "Corners have to be arcs");
+        assert!((area(&result[0]) - (50.0 + 30.0 + std::f64::consts::PI)).abs() < 1e-9, "Incorrect area of expanded rectangle");
+
+        let result = offset_polygon_arcs(&input, -1.0).unwrap();
+        assert!(result.len() == 1 && result[0].len() == 4 && (area(&result[0]) - 24.0).abs() < 1e-9, "Shrunk rectangle consists of four lines");
+    }
+    #[test]
+    fn concave() {
+        // shrinking an L-shape rounds its concave corner, where the arc intersects the shrunk edges exactly
+        let input: LineString<f64> = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 5.0), (5.0, 5.0), (5.0, 10.0), (0.0, 10.0), (0.0, 0.0)].into();
+        let result = offset_polygon_arcs(&input, -1.0).unwrap();
+        assert!(result.len() == 1, "Shrunk L-shape should be one path");
+        let arcs: Vec<_> = result[0].iter().filter(|segment| matches!(segment, PathSegment::Arc { .. })).collect();
+        assert!(arcs.len() == 1 && arcs[0].start() == Coordinate { x: 5.0, y: 4.0 } && arcs[0].end() == Coordinate { x: 4.0, y: 5.0 }, "Concave corner has to be a clockwise arc");
+        let expected = 40.0 - std::f64::consts::PI / 4.0;
+        assert!((area(&result[0]) - expected).abs() < 1e-9, "Incorrect area of shrunk L-shape");
+        // expanding closes the slot, where the arcs around its inner corners intersect each other
+        let slot: LineString<f64> = vec![(0.0, 0.0), (3.0, 0.0), (3.0, 4.0), (2.0, 4.0), (2.0, 1.0), (1.0, 1.0), (1.0, 4.0), (0.0, 4.0), (0.0, 0.0)].into();
+        let result = offset_polygon_arcs(&slot, 0.7).unwrap();
+        assert!(result.len() == 1 && result[0].len() == 11, "Expanded slot should be one path without the slot");
+        assert!(result[0].iter().map(|segment| segment.point_at(0.5)).all(|p| p.x < 1.0 || p.x > 2.0 || p.y > 4.5 || p.y < 0.0), "Slot has to be filled");
+    }
+}
@@ -0,0 +1,264 @@
+//! Reading closed polylines from DXF files and writing offset results as `LWPOLYLINE` entities.
+//!
+//! Only available with the `dxf` feature. Only the ASCII format is supported.
+
+use geo_types::Coordinate;
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::ops::{AddAssign, SubAssign};
+use std::fmt::Write;
+
+use super::{BulgeVertex, PathSegment, Path, path_from_bulges, bulges_from_path, offset_paths, path_area, ParseError, CombinatorialExplosionError};
+use super::curve::winding_number;
+use super::point_in_polygon::nest_by;
+
+/// The application name of the extended data that stores the source layer and handle of written entities.
+pub const APPLICATION: &str = "OFFSET_POLYGON";
+
+/// A closed polyline read from a `LWPOLYLINE` or `POLYLINE` entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyline<N: Num + Copy + NumCast + PartialOrd> {
+    /// The handle of the entity (group code 5), if it has one.
+    pub handle: Option<String>,
+    /// The layer of the entity (group code 8).
+    pub layer: String,
+    pub vertices: Vec<BulgeVertex<N>>,
+}
+
+/// A polyline that is being read. `POLYLINE` entities are followed by `VERTEX` entities until `SEQEND`.
+struct Entity<N: Num + Copy + NumCast + PartialOrd> {
+    polyline: Polyline<N>,
+    closed: bool,
+    sequence: bool,
+    /// Whether the group codes belong to a `VERTEX` of a `POLYLINE`.
+    vertex: bool,
+}
+
+fn error<T>(position: usize, message: &str) -> Result<T, ParseError> {
+    Err(ParseError {
+        position,
+        message: message.to_string(),
+    })
+}
+
+/// Reads all closed `LWPOLYLINE` and `POLYLINE` entities from the `ENTITIES` section of a DXF file. Open polylines and all other
+/// entities are skipped.
+pub fn read_dxf<N>(text: &str) -> Result<Vec<Polyline<N>>, ParseError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    // pairs of a group code and a value, each on its own line, with the byte offset of the code
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in text.split('\n') {
+        lines.push((offset, line.trim()));
+        offset += line.len() + 1;
+    }
+    if lines.last().map_or(false, |(_, line)| line.is_empty()) {
+        lines.pop();
+    }
+    if lines.len() % 2 != 0 {
+        return error(offset.min(text.len()), "Missing value for the last group code");
+    }
+
+    let mut polylines = Vec::new();
+    let mut current: Option<Entity<N>> = None;
+    let mut in_entities = false;
+    let mut section_start = false;
+    for pair in lines.chunks(2) {
+        let ((position, code), (value_position, value)) = (pair[0], pair[1]);
+        let code: i32 = match code.parse() {
+            Ok(code) => code,
+            Err(_) => return error(position, "Invalid group code"),
+        };
+        let number = || match value.parse::<f64>().ok().and_then(N::from_f64) {
+            Some(number) => Ok(number),
+            None => error(value_position, "Invalid number"),
+        };
+        if code == 0 {
+            if let Some(entity) = current.as_mut().filter(|entity| entity.sequence && value == "VERTEX") {
+                entity.vertex = true;
+                entity.polyline.vertices.push((Coordinate { x: N::zero(), y: N::zero() }, N::zero()));
+            } else if let Some(entity) = current.take() {
+                if entity.closed && entity.polyline.vertices.len() > 1 {
+                    polylines.push(entity.polyline);
+                }
+            }
+            section_start = value == "SECTION";
+            match value {
+                "ENDSEC" => in_entities = false,
+                "LWPOLYLINE" | "POLYLINE" if in_entities => current = Some(Entity {
+                    polyline: Polyline { handle: None, layer: "0".to_string(), vertices: Vec::new() },
+                    closed: false,
+                    sequence: value == "POLYLINE",
+                    vertex: false,
+                }),
+                _ => {},
+            }
+            continue;
+        }
+        if section_start && code == 2 {
+            in_entities = value == "ENTITIES";
+            section_start = false;
+            continue;
+        }
+        let entity = match current.as_mut() {
+            Some(entity) => entity,
+            None => continue,
+        };
+        match code {
+            5 if !entity.vertex => entity.polyline.handle = Some(value.to_string()),
+            8 if !entity.vertex => entity.polyline.layer = value.to_string(),
+            70 if !entity.vertex => entity.closed = value.parse::<i32>().map(|flags| flags & 1 != 0).unwrap_or(false),
+            // the coordinates of a POLYLINE itself only give its elevation
+            10 | 20 if entity.sequence && !entity.vertex => {},
+            10 if entity.sequence => entity.polyline.vertices.last_mut().unwrap().0.x = number()?,
+            10 => entity.polyline.vertices.push((Coordinate { x: number()?, y: N::zero() }, N::zero())),
+            20 => match entity.polyline.vertices.last_mut() {
+                Some(vertex) => vertex.0.y = number()?,
+                None => return error(value_position, "Coordinate without vertex"),
+            },
+            42 => match entity.polyline.vertices.last_mut() {
+                Some(vertex) => vertex.1 = number()?,
+                None => return error(value_position, "Bulge without vertex"),
+            },
+            _ => {},
+        }
+    }
+    if let Some(entity) = current {
+        if entity.closed && entity.polyline.vertices.len() > 1 {
+            polylines.push(entity.polyline);
+        }
+    }
+    Ok(polylines)
+}
+
+fn reversed<N>(path: &[PathSegment<N>]) -> Path<N>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    path.iter().rev().map(PathSegment::reversed).collect()
+}
+
+/// Expands or shrinks the polylines, see [`offset_bulge_polyline`](../fn.offset_bulge_polyline.html). The polylines are nested
+/// by the even-odd rule: polylines inside an odd number of others are holes, which are offset together with the polyline
+/// around them and shrink when it expands. Polylines may be oriented either way, a positive offset always expands the parts.
+/// All results of a part keep the handle and layer of its outer polyline, and are mirrored if that one is clockwise. The error
+/// is the same as the one of [`offset_polygon`](../fn.offset_polygon.html).
+pub fn offset_polylines<N>(polylines: &[Polyline<N>], offset: N) -> Result<Vec<Polyline<N>>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let paths: Vec<Path<N>> = polylines.iter().map(|polyline| path_from_bulges(&polyline.vertices)).collect();
+    let inside = |inner: usize, outer: usize| paths[inner].first().map(|segment| winding_number(&paths[outer], segment.start()) != 0).unwrap_or(false);
+    let mut results = Vec::new();
+    for (exterior, holes) in nest_by(paths.len(), inside) {
+        // the exterior counter-clockwise and the holes clockwise
+        let oriented = |idx: usize, counter_clockwise: bool| if (path_area(&paths[idx]) > N::zero()) == counter_clockwise { paths[idx].clone() } else { reversed(&paths[idx]) };
+        let part: Vec<Path<N>> = std::iter::once(oriented(exterior, true)).chain(holes.iter().map(|hole| oriented(*hole, false))).collect();
+        let clockwise = path_area(&paths[exterior]) < N::zero();
+        let polyline = &polylines[exterior];
+        results.extend(offset_paths(&part, offset)?.into_iter().map(|result| {
+            let result = if clockwise { reversed(&result) } else { result };
+            Polyline {
+                handle: polyline.handle.clone(),
+                layer: polyline.layer.clone(),
+                vertices: bulges_from_path(&result),
+            }
+        }));
+    }
+    Ok(results)
+}
+
+/// Writes the polylines as closed `LWPOLYLINE` entities on `layer`, in a DXF file of AutoCAD 2000 (`AC1015`) with the header
+/// variables `$ACADVER` and `$HANDSEED`. The layer and handle of each polyline are stored as extended data of
+/// [`APPLICATION`](constant.APPLICATION.html), both as strings (group code 1000). The handle isn't written as a handle reference,
+/// since it refers to an entity of the input file that doesn't exist in the output; the entities get new handles instead.
+pub fn write_dxf<N>(polylines: &[Polyline<N>], layer: &str) -> String
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    // handle 1 is the APPID table, 2 its entry for the application and the entities follow
+    let first_entity = 3;
+    let handle_seed = format!("{:X}", first_entity + polylines.len());
+    let mut text = String::new();
+    let mut pair = |code: i32, value: &str| writeln!(text, "{}\n{}", code, value).unwrap();
+    for (code, value) in &[(0, "SECTION"), (2, "HEADER"), (9, "$ACADVER"), (1, "AC1015"), (9, "$HANDSEED"), (5, handle_seed.as_str()), (0, "ENDSEC")] {
+        pair(*code, value);
+    }
+    // the application has to be registered for the extended data
+    for (code, value) in &[
+        (0, "SECTION"), (2, "TABLES"), (0, "TABLE"), (2, "APPID"), (5, "1"), (100, "AcDbSymbolTable"), (70, "1"),
+        (0, "APPID"), (5, "2"), (330, "1"), (100, "AcDbSymbolTableRecord"), (100, "AcDbRegAppTableRecord"), (2, APPLICATION), (70, "0"),
+        (0, "ENDTAB"), (0, "ENDSEC"), (0, "SECTION"), (2, "ENTITIES"),
+    ] {
+        pair(*code, value);
+    }
+    for (idx, polyline) in polylines.iter().enumerate() {
+        pair(0, "LWPOLYLINE");
+        pair(5, &format!("{:X}", first_entity + idx));
+        pair(100, "AcDbEntity");
+        pair(8, layer);
+        pair(100, "AcDbPolyline");
+        pair(90, &polyline.vertices.len().to_string());
+        pair(70, "1");
+        for (p, bulge) in &polyline.vertices {
+            pair(10, &p.x.to_f64().unwrap().to_string());
+            pair(20, &p.y.to_f64().unwrap().to_string());
+            if *bulge != N::zero() {
+                pair(42, &bulge.to_f64().unwrap().to_string());
+            }
+        }
+        pair(1001, APPLICATION);
+        pair(1000, &polyline.layer);
+        if let Some(handle) = &polyline.handle {
+            pair(1000, handle);
+        }
+    }
+    pair(0, "ENDSEC");
+    pair(0, "EOF");
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const INPUT: &str = "0\nSECTION\n2\nENTITIES\n\
+        0\nLWPOLYLINE\n5\n2A\n100\nAcDbEntity\n8\nPARTS\n100\nAcDbPolyline\n90\n4\n70\n1\n\
+        10\n0.0\n20\n0.0\n10\n10.0\n20\n0.0\n42\n1.0\n10\n10.0\n20\n2.0\n10\n0.0\n20\n2.0\n42\n1.0\n\
+        0\nLWPOLYLINE\n5\n2B\n8\nPARTS\n90\n2\n70\n0\n10\n0.0\n20\n0.0\n10\n5.0\n20\n5.0\n\
+        0\nPOLYLINE\n5\n30\n8\nHOLES\n66\n1\n10\n0.0\n20\n0.0\n30\n0.0\n70\n1\n\
+        0\nVERTEX\n5\n31\n8\nHOLES\n10\n20.0\n20\n0.0\n70\n0\n0\nVERTEX\n5\n32\n8\nHOLES\n10\n20.0\n20\n4.0\n\
+        0\nVERTEX\n5\n33\n8\nHOLES\n10\n24.0\n20\n4.0\n0\nVERTEX\n5\n34\n8\nHOLES\n10\n24.0\n20\n0.0\n0\nSEQEND\n\
+        0\nENDSEC\n0\nEOF\n";
+    #[test]
+    fn read() {
+        let polylines = read_dxf::<f64>(INPUT).unwrap();
+        assert!(polylines.len() == 2, "Only closed polylines should be read");
+        assert!(polylines[0].handle.as_deref() == Some("2A") && polylines[0].layer == "PARTS" && polylines[0].vertices[1] == (Coordinate { x: 10.0, y: 0.0 }, 1.0), "Incorrect LWPOLYLINE");
+        assert!(polylines[1].handle.as_deref() == Some("30") && polylines[1].layer == "HOLES" && polylines[1].vertices.len() == 4 && polylines[1].vertices[2].0 == Coordinate { x: 24.0, y: 4.0 }, "Incorrect POLYLINE");
+        assert!(read_dxf::<f64>("0\nSECTION\n2\nENTITIES\n0\nLWPOLYLINE\n10\nx\n").unwrap_err().position == 37, "Error has to point to the invalid number");
+    }
+    #[test]
+    fn offset() {
+        let polylines = read_dxf::<f64>(INPUT).unwrap();
+        let result = offset_polylines(&polylines, 1.0).unwrap();
+        assert!(result.len() == 2 && result[0].handle.as_deref() == Some("2A") && result[1].layer == "HOLES", "Results have to keep handle and layer");
+        // the stadium keeps its bulges, the clockwise square is expanded as well and gets arcs in its corners
+        assert!(result[0].vertices.iter().filter(|(_, bulge)| (bulge - 1.0).abs() < 1e-9).count() == 2, "Stadium has to keep its half circles");
+        assert!(result[1].vertices.len() == 8 && result[1].vertices.iter().all(|(p, _)| p.x >= 19.0 - 1e-9 && p.x <= 25.0 + 1e-9), "Clockwise square has to be expanded");
+        assert!(result[1].vertices.iter().filter(|(_, bulge)| *bulge != 0.0).all(|(_, bulge)| *bulge < 0.0), "Clockwise square has to stay clockwise");
+
+        let text = write_dxf(&result, "OFFSET");
+        assert!(text.contains("8\nOFFSET\n") && text.contains("1000\nPARTS\n") && text.contains("1000\n2A\n"), "Source layer and handle have to be kept");
+        assert!(text.starts_with("0\nSECTION\n2\nHEADER\n9\n$ACADVER\n1\nAC1015\n9\n$HANDSEED\n5\n5\n"), "Header has to give the version and the next free handle");
+        assert!(text.contains("0\nLWPOLYLINE\n5\n3\n") && text.contains("0\nLWPOLYLINE\n5\n4\n"), "Entities have to get their own handles");
+        let written = read_dxf::<f64>(&text).unwrap();
+        assert!(written.len() == 2 && written.iter().zip(result.iter()).all(|(a, b)| a.layer == "OFFSET" && a.vertices == b.vertices), "Written polylines have to round-trip");
+    }
+    #[test]
+    fn holes() {
+        let square = |handle: &str, x0: f64, x1: f64| format!(
+            "0\nLWPOLYLINE\n5\n{}\n8\nPARTS\n90\n4\n70\n1\n10\n{}\n20\n{}\n10\n{}\n20\n{}\n10\n{}\n20\n{}\n10\n{}\n20\n{}\n",
+            handle, x0, x0, x1, x0, x1, x1, x0, x1,
+        );
+        // both squares are counter-clockwise, the inner one is a hole nevertheless
+        let text = format!("0\nSECTION\n2\nENTITIES\n{}{}0\nENDSEC\n0\nEOF\n", square("40", 0.0, 10.0), square("41", 3.0, 7.0));
+        let polylines = read_dxf::<f64>(&text).unwrap();
+        let result = offset_polylines(&polylines, 1.0).unwrap();
+        assert!(result.len() == 2 && result.iter().all(|polyline| polyline.handle.as_deref() == Some("40")), "Results have to keep the handle of the outer polyline");
+        assert!(result[1].vertices.iter().all(|(p, bulge)| *bulge == 0.0 && p.x >= 4.0 - 1e-9 && p.x <= 6.0 + 1e-9), "Hole has to shrink");
+        assert!(offset_polylines(&polylines, 2.5).unwrap().len() == 1, "Hole has to vanish");
+    }
+}
@@ -16,3 +16,47 @@ impl fmt::Debug for CombinatorialExplosionError {
 
 impl std::error::Error for CombinatorialExplosionError {
 }
+
+/// An error of the offset functions that validate their arguments.
+#[derive(Debug)]
+pub enum OffsetError {
+    /// Resolving the intersections of the raw offset curve took too many steps.
+    CombinatorialExplosion(CombinatorialExplosionError),
+    /// An argument is out of range or doesn't match the size of the input.
+    InvalidArgument(String),
+}
+
+impl fmt::Display for OffsetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OffsetError::CombinatorialExplosion(error) => error.fmt(f),
+            OffsetError::InvalidArgument(message) => write!(f, "Invalid argument: {}.", message),
+        }
+    }
+}
+
+impl std::error::Error for OffsetError {
+}
+
+impl From<CombinatorialExplosionError> for OffsetError {
+    fn from(error: CombinatorialExplosionError) -> OffsetError {
+        OffsetError::CombinatorialExplosion(error)
+    }
+}
+
+/// An error while reading geometries from a text format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// The byte offset in the input where the error was found.
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}.", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {
+}
@@ -0,0 +1,40 @@
+/// Decides which areas are filled, based on their winding number (how often the rings wind around them counter-clockwise).
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum FillRule {
+    /// Areas with a winding number other than zero.
+    NonZero,
+    /// Areas with an odd winding number.
+    EvenOdd,
+    /// Areas with a positive winding number.
+    Positive,
+    /// Areas with a negative winding number.
+    Negative,
+    /// Areas with a winding number of at least two or at most minus two, which are covered by the rings more than once.
+    AbsGeqTwo,
+    /// Areas with a winding number of at least the given value.
+    AtLeast(isize),
+}
+
+impl FillRule {
+    /// Returns whether an area with the given winding number is filled.
+    pub fn is_filled(self, winding_number: isize) -> bool {
+        match self {
+            FillRule::NonZero => winding_number != 0,
+            FillRule::EvenOdd => winding_number % 2 != 0,
+            FillRule::Positive => winding_number > 0,
+            FillRule::Negative => winding_number < 0,
+            FillRule::AbsGeqTwo => winding_number.abs() >= 2,
+            FillRule::AtLeast(k) => winding_number >= k,
+        }
+    }
+
+    /// Returns the winding number of the filled side of a boundary between areas with the given winding numbers on its left and
+    /// right side, or `None` if both sides are filled or both are not.
+    pub(crate) fn filled_side(self, left: isize, right: isize) -> Option<isize> {
+        match (self.is_filled(left), self.is_filled(right)) {
+            (true, false) => Some(left),
+            (false, true) => Some(right),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Reading features from GeoJSON, offsetting them by a distance taken from their properties and writing them back.
+//!
+//! Only available with the `geojson` feature. `Polygon` and `MultiPolygon` geometries are supported, features without a geometry
+//! (`"geometry": null`) are passed through unchanged.
+
+use geo_types::{LineString, Coordinate, Polygon, MultiPolygon};
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use serde_json::{Value, Map, json};
+use std::ops::{AddAssign, SubAssign};
+use std::fmt;
+
+use super::{offset_polygons, ParseError};
+
+/// The property added to every offset feature, containing the distance it was offset by.
+pub const DISTANCE_PROPERTY: &str = "offset_distance";
+
+/// A feature with a polygonal geometry. A `Polygon` geometry is read as a multipolygon with one polygon, a `null` geometry as `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Feature<N: Num + Copy + NumCast + PartialOrd> {
+    pub id: Option<Value>,
+    pub geometry: Option<MultiPolygon<N>>,
+    pub properties: Map<String, Value>,
+}
+
+/// An error while reading or offsetting GeoJSON.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoJsonError {
+    /// The input is not valid JSON.
+    Parse(ParseError),
+    /// The feature with this index in the input can't be read or offset.
+    Feature {
+        index: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for GeoJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GeoJsonError::Parse(error) => error.fmt(f),
+            GeoJsonError::Feature { index, message } => write!(f, "Feature {}: {}.", index, message),
+        }
+    }
+}
+
+impl std::error::Error for GeoJsonError {
+}
+
+fn feature_error<T>(index: usize, message: &str) -> Result<T, GeoJsonError> {
+    Err(GeoJsonError::Feature {
+        index,
+        message: message.to_string(),
+    })
+}
+
+fn read_ring<N>(value: &Value) -> Option<LineString<N>>
+        where N: Num + Copy + NumCast + PartialOrd + FromPrimitive {
+    value.as_array()?.iter().map(|position| {
+        let position = position.as_array()?;
+        Some(Coordinate {
+            x: N::from_f64(position.first()?.as_f64()?)?,
+            y: N::from_f64(position.get(1)?.as_f64()?)?,
+        })
+    }).collect::<Option<Vec<_>>>().map(LineString)
+}
+
+fn read_polygon<N>(value: &Value) -> Option<Polygon<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    let mut rings = value.as_array()?.iter().map(read_ring).collect::<Option<Vec<_>>>()?;
+    if rings.is_empty() {
+        return None;
+    }
+    let exterior = rings.remove(0);
+    Some(Polygon::new(exterior, rings))
+}
+
+fn read_feature<N>(value: &Value, index: usize) -> Result<Feature<N>, GeoJsonError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    if value.get("type").and_then(Value::as_str) != Some("Feature") {
+        return feature_error(index, "Expected a Feature");
+    }
+    let geometry = match value.get("geometry") {
+        Some(Value::Null) => return Ok(Feature {
+            id: value.get("id").cloned(),
+            geometry: None,
+            properties: value.get("properties").and_then(Value::as_object).cloned().unwrap_or_default(),
+        }),
+        Some(geometry) if geometry.is_object() => geometry,
+        _ => return feature_error(index, "Missing geometry"),
+    };
+    let coordinates = geometry.get("coordinates").unwrap_or(&Value::Null);
+    let polygons = match geometry.get("type").and_then(Value::as_str) {
+        Some("Polygon") => read_polygon(coordinates).map(|polygon| vec![polygon]),
+        Some("MultiPolygon") => coordinates.as_array().and_then(|polygons| polygons.iter().map(read_polygon).collect()),
+        Some(kind) => return feature_error(index, &format!("Unsupported geometry type {}", kind)),
+        None => return feature_error(index, "Missing geometry type"),
+    };
+    let polygons = match polygons {
+        Some(polygons) => polygons,
+        None => return feature_error(index, "Invalid coordinates"),
+    };
+    Ok(Feature {
+        id: value.get("id").cloned(),
+        geometry: Some(MultiPolygon(polygons)),
+        properties: value.get("properties").and_then(Value::as_object).cloned().unwrap_or_default(),
+    })
+}
+
+/// Parses a `Feature` or a `FeatureCollection`. Errors in features report the index of the feature in the collection.
+pub fn read_features<N>(text: &str) -> Result<Vec<Feature<N>>, GeoJsonError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    read_feature_results(text)?.into_iter().collect()
+}
+
+/// Like [`read_features`](fn.read_features.html), but reads every feature on its own, so that one invalid feature doesn't
+/// prevent reading the others. Only input that isn't valid JSON or has no features at all is an error of the whole result.
+pub fn read_feature_results<N>(text: &str) -> Result<Vec<Result<Feature<N>, GeoJsonError>>, GeoJsonError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    let value: Value = serde_json::from_str(text).map_err(|error| {
+        // serde_json reports lines and columns, both starting at 1
+        let line_start: usize = text.split('\n').take(error.line().saturating_sub(1)).map(|line| line.len() + 1).sum();
+        GeoJsonError::Parse(ParseError {
+            position: line_start + error.column().saturating_sub(1),
+            message: error.to_string(),
+        })
+    })?;
+    match value.get("type").and_then(Value::as_str) {
+        Some("FeatureCollection") => match value.get("features").and_then(Value::as_array) {
+            Some(features) => Ok(features.iter().enumerate().map(|(index, feature)| read_feature(feature, index)).collect()),
+            None => feature_error(0, "Missing features"),
+        },
+        _ => Ok(vec![read_feature(&value, 0)]),
+    }
+}
+
+fn write_ring<N>(ring: &LineString<N>) -> Value
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    Value::Array(ring.0.iter().map(|p| json!([p.x.to_f64().unwrap(), p.y.to_f64().unwrap()])).collect())
+}
+
+fn write_polygon<N>(polygon: &Polygon<N>) -> Value
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    Value::Array(std::iter::once(polygon.exterior()).chain(polygon.interiors()).map(write_ring).collect())
+}
+
+/// Writes the features as a `FeatureCollection`. Geometries with one polygon are written as `Polygon`, all others as `MultiPolygon`.
+/// Features without a geometry are written with `"geometry": null`.
+pub fn write_features<N>(features: &[Feature<N>]) -> String
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let features: Vec<Value> = features.iter().map(|feature| {
+        let geometry = match &feature.geometry {
+            Some(geometry) if geometry.0.len() == 1 => json!({ "type": "Polygon", "coordinates": write_polygon(&geometry.0[0]) }),
+            Some(geometry) => json!({ "type": "MultiPolygon", "coordinates": geometry.0.iter().map(write_polygon).collect::<Vec<_>>() }),
+            None => Value::Null,
+        };
+        let mut object = Map::new();
+        object.insert("type".to_string(), json!("Feature"));
+        if let Some(id) = &feature.id {
+            object.insert("id".to_string(), id.clone());
+        }
+        object.insert("geometry".to_string(), geometry);
+        object.insert("properties".to_string(), Value::Object(feature.properties.clone()));
+        Value::Object(object)
+    }).collect();
+    json!({ "type": "FeatureCollection", "features": features }).to_string()
+}
+
+/// Offsets one feature by the number in its property `property`, or by `default` if there's no property name or the feature doesn't
+/// have that property. The result keeps the id and the properties of the feature and adds the distance used as
+/// [`DISTANCE_PROPERTY`](constant.DISTANCE_PROPERTY.html). Features without a geometry stay without one.
+///
+/// # Arguments
+///
+/// * `feature` - The feature to offset, its polygons may have holes and overlap each other.
+/// * `index` - The index of the feature, which is reported in errors.
+/// * `property` - The name of the property containing the distance. Positive distances expand the polygons, negative ones shrink them.
+/// * `default` - The distance for features without the property. If it's `None`, those features are an error.
+/// * `arcdetail` - Defines how many points should be added in a sharp corner. This number is the number of vertices inserted if it's a full circle.
+pub fn offset_feature<N>(feature: &Feature<N>, index: usize, property: Option<&str>, default: Option<N>, arcdetail: N) -> Result<Feature<N>, GeoJsonError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let distance = match property.and_then(|property| feature.properties.get(property).map(|value| (property, value))) {
+        Some((property, value)) => match value.as_f64().and_then(N::from_f64) {
+            Some(distance) => distance,
+            None => return feature_error(index, &format!("Property {} is not a number", property)),
+        },
+        None => match (default, property) {
+            (Some(distance), _) => distance,
+            (None, Some(property)) => return feature_error(index, &format!("Missing property {}", property)),
+            (None, None) => return feature_error(index, "Missing distance"),
+        },
+    };
+    let mut properties = feature.properties.clone();
+    properties.insert(DISTANCE_PROPERTY.to_string(), json!(distance.to_f64().unwrap()));
+    let arcstep = N::from_f32(2.0).unwrap() * N::PI() / arcdetail;
+    let geometry = match &feature.geometry {
+        Some(geometry) => match offset_polygons(geometry, distance, arcstep) {
+            Ok(geometry) => Some(geometry),
+            Err(error) => return feature_error(index, error.to_string().trim_end_matches('.')),
+        },
+        None => None,
+    };
+    Ok(Feature {
+        id: feature.id.clone(),
+        geometry,
+        properties,
+    })
+}
+
+/// Offsets every feature with [`offset_feature`](fn.offset_feature.html), stopping at the first feature that fails.
+pub fn offset_features<N>(features: &[Feature<N>], property: &str, default: Option<N>, arcdetail: N) -> Result<Vec<Feature<N>>, GeoJsonError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    features.iter().enumerate().map(|(index, feature)| offset_feature(feature, index, Some(property), default, arcdetail)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const INPUT: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            { "type": "Feature", "id": 7, "properties": { "name": "square", "buffer": 1 },
+              "geometry": { "type": "Polygon", "coordinates": [[[0, 0], [10, 0], [10, 10], [0, 10], [0, 0]], [[2, 2], [2, 8], [8, 8], [8, 2], [2, 2]]] } },
+            { "type": "Feature", "properties": { "name": "pair" },
+              "geometry": { "type": "MultiPolygon", "coordinates": [[[[20, 0], [22, 0], [22, 2], [20, 2], [20, 0]]], [[[23, 0], [25, 0], [25, 2], [23, 2], [23, 0]]]] } }
+        ]
+    }"#;
+    #[test]
+    fn offset() {
+        let features = read_features::<f64>(INPUT).unwrap();
+        let geometries: Vec<&MultiPolygon<f64>> = features.iter().filter_map(|feature| feature.geometry.as_ref()).collect();
+        assert!(geometries.len() == 2 && geometries[0].0[0].interiors().len() == 1 && geometries[1].0.len() == 2, "Incorrect features");
+        assert!(offset_features(&features, "buffer", None, 16.0) == Err(GeoJsonError::Feature { index: 1, message: "Missing property buffer".to_string() }), "Missing distance has to be reported");
+
+        let result = offset_features(&features, "buffer", Some(0.6), 16.0).unwrap();
+        assert!(result[0].id == Some(json!(7)) && result[0].properties["name"] == json!("square") && result[0].properties[DISTANCE_PROPERTY] == json!(1.0), "Properties have to be kept");
+        let geometries: Vec<&MultiPolygon<f64>> = result.iter().filter_map(|feature| feature.geometry.as_ref()).collect();
+        assert!(geometries[0].0.len() == 1 && geometries[0].0[0].interiors()[0].0.iter().all(|p| p.x >= 3.0 - 1e-9 && p.x <= 7.0 + 1e-9), "Hole has to shrink");
+        // the two squares of the second feature merge
+        assert!(geometries[1].0.len() == 1 && result[1].properties[DISTANCE_PROPERTY] == json!(0.6), "Incorrect default distance");
+
+        let written: Value = serde_json::from_str(&write_features(&result)).unwrap();
+        assert!(written["type"] == "FeatureCollection" && written["features"][0]["geometry"]["type"] == "Polygon", "Incorrect output");
+        assert!(read_features::<f64>(&write_features(&result)).unwrap() == result, "Features have to round-trip");
+    }
+    #[test]
+    fn errors() {
+        let error = read_features::<f64>("{\n  \"type\": x }").unwrap_err();
+        assert!(match error { GeoJsonError::Parse(error) => error.position == 12, _ => false }, "Parse error has to point to the invalid character");
+        let point = r#"{ "type": "Feature", "properties": {}, "geometry": { "type": "Point", "coordinates": [0, 0] } }"#;
+        assert!(read_features::<f64>(point) == Err(GeoJsonError::Feature { index: 0, message: "Unsupported geometry type Point".to_string() }), "Points are not supported");
+    }
+    #[test]
+    fn feature_results() {
+        let input = r#"{ "type": "FeatureCollection", "features": [
+            { "type": "Feature", "id": "empty", "properties": { "buffer": 1 }, "geometry": null },
+            { "type": "Feature", "properties": {}, "geometry": { "type": "Point", "coordinates": [0, 0] } },
+            { "type": "Feature", "properties": {}, "geometry": { "type": "Polygon", "coordinates": [[[0, 0], [4, 0], [4, 4], [0, 4], [0, 0]]] } }
+        ] }"#;
+        let features = read_feature_results::<f64>(input).unwrap();
+        assert!(features.len() == 3 && features[1].is_err() && features[2].is_ok(), "Invalid features must not prevent reading the others");
+        let empty = features[0].as_ref().unwrap();
+        assert!(empty.geometry.is_none(), "Null geometries have to be read as None");
+        let result = offset_feature(empty, 0, Some("buffer"), None, 16.0).unwrap();
+        assert!(result.geometry.is_none() && result.id == Some(json!("empty")) && result.properties[DISTANCE_PROPERTY] == json!(1.0), "Features without geometry have to be passed through");
+        let written: Value = serde_json::from_str(&write_features(&[result])).unwrap();
+        assert!(written["features"][0]["geometry"].is_null(), "Missing geometries have to be written as null");
+        assert!(offset_feature(features[2].as_ref().unwrap(), 2, None, None, 16.0) == Err(GeoJsonError::Feature { index: 2, message: "Missing distance".to_string() }), "Missing distance has to be reported");
+    }
+    #[test]
+    fn empty_rings() {
+        let input = r#"{ "type": "FeatureCollection", "features": [
+            { "type": "Feature", "properties": {}, "geometry": { "type": "Polygon", "coordinates": [[]] } },
+            { "type": "Feature", "properties": {}, "geometry": { "type": "Polygon", "coordinates": [[[0, 0], [4, 0], [4, 4], [0, 4], [0, 0]], []] } }
+        ] }"#;
+        let result = offset_features(&read_features::<f64>(input).unwrap(), "buffer", Some(1.0), 16.0).unwrap();
+        assert!(result[0].geometry.as_ref().unwrap().0.is_empty(), "An empty ring has to result in an empty geometry");
+        assert!(result[1].geometry.as_ref().unwrap().0.len() == 1 && result[1].geometry.as_ref().unwrap().0[0].interiors().is_empty(), "An empty hole has to be ignored");
+    }
+}
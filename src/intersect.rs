@@ -1,51 +1,134 @@
+//! Intersection tests between segments, rays and polylines.
+
 use geo_types::{LineString, Coordinate};
 use num_traits::{Num, NumCast, float::{Float, FloatConst}};
 
+/// An intersection of a segment or ray with a polyline.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct IntersectionResult<N: Num + Copy + NumCast + PartialOrd> {
+    /// The position of the intersection along the segment or ray (`0` at its start, `1` at its end or after its direction vector).
     pub u: N,
+    /// The position of the intersection along the segment of the polyline that was hit (`0` at its start, `1` at its end).
     pub t: N,
     pub point: Coordinate<N>,
+    /// The index of the segment of the polyline that was hit (segment `i` goes from coordinate `i` to coordinate `i+1`).
     pub index: usize,
 }
 
-fn cross_product<N>(a: Coordinate<N>, b: Coordinate<N>) -> N
+/// The range of positions along the segments of the polyline that count as a hit, `0` is the start of a segment and `1` its end.
+/// Both ends of the range are inclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParameterRange<N> {
+    pub min: N,
+    pub max: N,
+}
+
+impl<N> ParameterRange<N> where N: Num + Copy + NumCast + PartialOrd + Float {
+    /// The whole segment including both of its end points.
+    pub fn inclusive() -> ParameterRange<N> {
+        ParameterRange {
+            min: N::zero(),
+            max: N::one(),
+        }
+    }
+
+    /// The segment without the parts closer than `tolerance` (relative to its length) to its end points.
+    pub fn exclude_endpoints(tolerance: N) -> ParameterRange<N> {
+        ParameterRange {
+            min: tolerance,
+            max: N::one() - tolerance,
+        }
+    }
+
+    fn contains(&self, t: N) -> bool {
+        t >= self.min && t <= self.max
+    }
+}
+
+/// The intersection of two segments.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SegmentIntersection<N: Num + Copy + NumCast + PartialOrd> {
+    /// The segments cross or touch in a single point, at position `u` along the first and `t` along the second segment.
+    Point {
+        point: Coordinate<N>,
+        u: N,
+        t: N,
+    },
+    /// The segments are collinear and overlap between the two points, given in the direction of the first segment together with
+    /// their positions along it.
+    Overlap {
+        start: Coordinate<N>,
+        end: Coordinate<N>,
+        u_start: N,
+        u_end: N,
+    },
+}
+
+pub(crate) fn cross_product<N>(a: Coordinate<N>, b: Coordinate<N>) -> N
         where N: Num + Copy + NumCast + PartialOrd {
     a.x * b.y - a.y * b.x
 }
 
-// https://stackoverflow.com/questions/563198/how-do-you-detect-where-two-line-segments-intersect/565282#565282
+pub(crate) fn dot_product<N>(a: Coordinate<N>, b: Coordinate<N>) -> N
+        where N: Num + Copy + NumCast + PartialOrd {
+    a.x * b.x + a.y * b.y
+}
+
+pub(crate) fn sub<N>(a: Coordinate<N>, b: Coordinate<N>) -> Coordinate<N>
+        where N: Num + Copy + NumCast + PartialOrd {
+    Coordinate {
+        x: a.x - b.x,
+        y: a.y - b.y,
+    }
+}
+
+/// Returns the positions of the intersection along the query (start at `start`, direction `s`) and along the segment from `p0` to
+/// `p1`, or `None` if they are parallel.
+fn line_parameters<N>(start: Coordinate<N>, s: Coordinate<N>, p0: Coordinate<N>, p1: Coordinate<N>) -> Option<(N, N)>
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let r = sub(p1, p0);
+    let rxs = cross_product(r, s);
+    if rxs.abs() < N::epsilon() {
+        return None;
+    }
+    let q_p = sub(start, p0);
+    Some((cross_product(q_p, r) / rxs, cross_product(q_p, s) / rxs))
+}
+
+/// Finds the first intersection of the segment from `start` to `end` with the polyline, ignoring intersections at `start` itself.
+/// When `exclude_points` is set, hits close to the vertices of the polyline are ignored.
 pub fn intersect<N>(start: Coordinate<N>, end: Coordinate<N>, line: &LineString<N>, exclude_points: bool) -> Option<IntersectionResult<N>>
         where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst {
+    let range = if exclude_points {
+        ParameterRange {
+            min: N::from(0.00001).unwrap(),
+            max: N::from(0.999999).unwrap(),
+        }
+    } else {
+        ParameterRange::inclusive()
+    };
+    nearest_intersection(start, end, line, range)
+}
+
+/// Like [`intersect`](fn.intersect.html), but only hits within `range` along the segments of the polyline count.
+// https://stackoverflow.com/questions/563198/how-do-you-detect-where-two-line-segments-intersect/565282#565282
+pub fn nearest_intersection<N>(start: Coordinate<N>, end: Coordinate<N>, line: &LineString<N>, range: ParameterRange<N>) -> Option<IntersectionResult<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst {
     let mut intersection_u = N::from(1.0).unwrap();
     let mut intersection_t = None;
     let mut intersection_point = None;
     let mut intersection_index = None;
-    let s = Coordinate {
-        x: end.x - start.x,
-        y: end.y - start.y,
-    };
+    let s = sub(end, start);
 
-    for idx in 0..(line.0.len()-1) {
-        let p0 = line.0[idx];
-        let p1 = line.0[idx+1];
-        let r = Coordinate {
-            x: p1.x - p0.x,
-            y: p1.y - p0.y,
+    for (idx, segment) in line.0.windows(2).enumerate() {
+        let (u, t) = match line_parameters(start, s, segment[0], segment[1]) {
+            Some(parameters) => parameters,
+            None => continue,
         };
-        let rxs = cross_product(r, s);
-        if rxs.abs() < N::epsilon() {
-            continue;
-        }
-        let q_p = Coordinate {
-            x: start.x - p0.x,
-            y: start.y - p0.y,
-        };
-        let u = cross_product(q_p, r) / rxs;
         if u < N::epsilon() || u > intersection_u {
             continue;
         }
-        let t = cross_product(q_p, s) / rxs;
-        if (!exclude_points && (t.is_sign_negative() || t > N::from(1.0).unwrap())) || (exclude_points && (t < N::from(0.00001).unwrap() || t > N::from(0.999999).unwrap())) {
+        if !range.contains(t) {
             continue;
         }
         intersection_u = u;
@@ -64,3 +147,130 @@ pub fn intersect<N>(start: Coordinate<N>, end: Coordinate<N>, line: &LineString<
         index: intersection_index.unwrap(),
     })
 }
+
+/// Collects the intersections with positions along the query in `(epsilon, max_u]`, sorted by that position.
+fn collect_intersections<N>(start: Coordinate<N>, s: Coordinate<N>, line: &LineString<N>, range: ParameterRange<N>, max_u: Option<N>) -> Vec<IntersectionResult<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let mut intersections: Vec<IntersectionResult<N>> = line.0.windows(2).enumerate().filter_map(|(index, segment)| {
+        line_parameters(start, s, segment[0], segment[1]).and_then(|(u, t)| {
+            if u < N::epsilon() || max_u.map(|max_u| u > max_u).unwrap_or(false) || !range.contains(t) {
+                None
+            } else {
+                Some(IntersectionResult {
+                    u,
+                    t,
+                    point: Coordinate {
+                        x: start.x + u * s.x,
+                        y: start.y + u * s.y,
+                    },
+                    index,
+                })
+            }
+        })
+    }).collect();
+    intersections.sort_by(|a, b| a.u.partial_cmp(&b.u).unwrap().then(a.index.cmp(&b.index)));
+    intersections
+}
+
+/// Finds all intersections of the segment from `start` to `end` with the polyline, sorted by their distance from `start`.
+/// Intersections at `start` itself are ignored, only hits within `range` along the segments of the polyline count.
+pub fn all_intersections<N>(start: Coordinate<N>, end: Coordinate<N>, line: &LineString<N>, range: ParameterRange<N>) -> Vec<IntersectionResult<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    collect_intersections(start, sub(end, start), line, range, Some(N::one()))
+}
+
+/// Finds all intersections of the ray starting at `origin` in the given direction with the polyline, sorted by their distance
+/// from `origin`. The position `u` of the results is in multiples of `direction`.
+pub fn ray_intersections<N>(origin: Coordinate<N>, direction: Coordinate<N>, line: &LineString<N>, range: ParameterRange<N>) -> Vec<IntersectionResult<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    collect_intersections(origin, direction, line, range, None)
+}
+
+/// Finds the first intersection of the ray starting at `origin` in the given direction with the polyline.
+pub fn nearest_ray_intersection<N>(origin: Coordinate<N>, direction: Coordinate<N>, line: &LineString<N>, range: ParameterRange<N>) -> Option<IntersectionResult<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    ray_intersections(origin, direction, line, range).into_iter().next()
+}
+
+/// Intersects the segment from `a0` to `a1` with the segment from `b0` to `b1`, including their end points. Collinear segments
+/// that share more than a single point are reported as an overlap. `tolerance` is the distance up to which the segments count
+/// as collinear.
+pub fn segment_intersection<N>(a0: Coordinate<N>, a1: Coordinate<N>, b0: Coordinate<N>, b1: Coordinate<N>, tolerance: N) -> Option<SegmentIntersection<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let (s, r) = (sub(a1, a0), sub(b1, b0));
+    let (len_s, len_r) = ((s.x * s.x + s.y * s.y).sqrt(), (r.x * r.x + r.y * r.y).sqrt());
+    if len_s < N::epsilon() || len_r < N::epsilon() {
+        return None;
+    }
+    let distance = |p: Coordinate<N>| cross_product(sub(p, a0), s).abs() / len_s;
+    if distance(b0) <= tolerance && distance(b1) <= tolerance { // collinear
+        let position = |p: Coordinate<N>| { let d = sub(p, a0); (d.x * s.x + d.y * s.y) / (len_s * len_s) };
+        let (t0, t1) = (position(b0), position(b1));
+        let (u_start, u_end) = (t0.min(t1).max(N::zero()), t0.max(t1).min(N::one()));
+        let point = |u: N| Coordinate {
+            x: a0.x + u * s.x,
+            y: a0.y + u * s.y,
+        };
+        let length = (u_end - u_start) * len_s;
+        return if length < -tolerance {
+            None
+        } else if length <= tolerance {
+            let u = (u_start + u_end) / (N::one() + N::one());
+            let t = if (t1 - t0).abs() > N::zero() { (u - t0) / (t1 - t0) } else { N::zero() };
+            Some(SegmentIntersection::Point { point: point(u), u, t })
+        } else {
+            Some(SegmentIntersection::Overlap { start: point(u_start), end: point(u_end), u_start, u_end })
+        };
+    }
+    let (u, t) = line_parameters(a0, s, b0, b1)?;
+    let inside = |value: N| value >= N::zero() && value <= N::one();
+    if inside(u) && inside(t) {
+        Some(SegmentIntersection::Point {
+            point: Coordinate {
+                x: a0.x + u * s.x,
+                y: a0.y + u * s.y,
+            },
+            u,
+            t,
+        })
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn zigzag() -> LineString<f64> {
+        vec![(0.0, -1.0), (1.0, 1.0), (2.0, -1.0), (3.0, 1.0), (4.0, -1.0)].into()
+    }
+    #[test]
+    fn polyline() {
+        let (start, end) = (Coordinate { x: -1.0, y: 0.0 }, Coordinate { x: 3.0, y: 0.0 });
+        let nearest = nearest_intersection(start, end, &zigzag(), ParameterRange::inclusive()).unwrap();
+        assert!(nearest.index == 0 && (nearest.point.x - 0.5).abs() < 1e-12, "Incorrect nearest intersection");
+        assert!(nearest_intersection(start, end, &LineString(Vec::new()), ParameterRange::inclusive()).is_none(), "Empty polylines have no intersections");
+        let all = all_intersections(start, end, &zigzag(), ParameterRange::inclusive());
+        assert!(all.iter().map(|hit| hit.index).collect::<Vec<_>>() == vec![0, 1, 2], "Incorrect intersections of segment");
+        let ray = ray_intersections(start, Coordinate { x: 1.0, y: 0.0 }, &zigzag(), ParameterRange::inclusive());
+        assert!(ray.len() == 4 && (ray[3].u - 4.5).abs() < 1e-12, "Incorrect intersections of ray");
+        // hitting the polyline exactly at a vertex
+        let (start, end) = (Coordinate { x: 1.0, y: 2.0 }, Coordinate { x: 1.0, y: 0.0 });
+        assert!(all_intersections(start, end, &zigzag(), ParameterRange::inclusive()).len() == 2, "Vertex hit has to be reported for both segments");
+        assert!(all_intersections(start, end, &zigzag(), ParameterRange::exclude_endpoints(1e-9)).is_empty(), "Vertex hit has to be excluded");
+    }
+    #[test]
+    fn segments() {
+        let (a0, a1) = (Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 4.0, y: 0.0 });
+        match segment_intersection(a0, a1, Coordinate { x: 1.0, y: -1.0 }, Coordinate { x: 1.0, y: 1.0 }, 1e-9) {
+            Some(SegmentIntersection::Point { point, u, t }) => assert!((point.x - 1.0).abs() < 1e-12 && (u - 0.25).abs() < 1e-12 && (t - 0.5).abs() < 1e-12, "Incorrect crossing"),
+            _ => panic!("Crossing segments have to intersect"),
+        }
+        match segment_intersection(a0, a1, Coordinate { x: 6.0, y: 0.0 }, Coordinate { x: 3.0, y: 0.0 }, 1e-9) {
+            Some(SegmentIntersection::Overlap { start, end, .. }) => assert!((start.x - 3.0).abs() < 1e-12 && (end.x - 4.0).abs() < 1e-12, "Incorrect overlap"),
+            _ => panic!("Collinear segments have to overlap"),
+        }
+        assert!(segment_intersection(a0, a1, Coordinate { x: 5.0, y: 0.0 }, Coordinate { x: 6.0, y: 0.0 }, 1e-9).is_none(), "Disjoint collinear segments must not intersect");
+        assert!(segment_intersection(a0, a1, Coordinate { x: 0.0, y: 1.0 }, Coordinate { x: 4.0, y: 1.0 }, 1e-9).is_none(), "Parallel segments must not intersect");
+    }
+}
@@ -33,14 +33,58 @@
 //!
 //! Note that polygons have to be closed (the last coordinate has to be the same as the first one), otherwise you will get some strange results.
 
-use geo_types::{LineString, Coordinate};
+use geo_types::{LineString, Coordinate, Polygon, MultiPolygon};
 use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
-use std::ops::{AddAssign, SubAssign};
+use std::ops::{AddAssign, SubAssign, Range};
 
 mod error;
-pub use error::CombinatorialExplosionError;
-mod intersect;
+pub use error::{CombinatorialExplosionError, OffsetError, ParseError};
+pub mod intersect;
 use intersect::intersect;
+mod buffer;
+pub use buffer::variable_width_buffer;
+mod anisotropic;
+pub use anisotropic::offset_polygon_anisotropic;
+mod minkowski;
+pub use minkowski::{minkowski_sum_convex, minkowski_sum};
+mod morphology;
+pub use morphology::{open, close};
+mod band;
+pub use band::offset_band;
+mod fill_rule;
+pub use fill_rule::FillRule;
+mod rings;
+pub use rings::offset_rings;
+mod resolve;
+pub use resolve::{resolve_self_intersections, offset_self_intersecting};
+pub mod point_in_polygon;
+use point_in_polygon::winding_number;
+mod provenance;
+pub use provenance::{offset_polygon_with_provenance, Provenance, Origin, LineStringWithProvenance};
+mod trace;
+pub use trace::{offset_polygon_traced, OffsetTrace, TraceIntersection, TraceRegion};
+mod attributes;
+pub use attributes::{offset_polygon_with_attributes, VertexAttribute, LineStringWithAttributes};
+mod curve;
+pub use curve::{offset_polygon_arcs, offset_path, offset_paths, flatten_path, path_area, PathSegment, Path};
+mod bulge;
+pub use bulge::{path_from_bulges, bulges_from_path, offset_bulge_polyline, BulgeVertex};
+mod bezier;
+pub use bezier::{offset_bezier, flatten_bezier, BezierSegment};
+pub mod toolpath;
+#[cfg(feature = "svg")]
+pub mod svg;
+#[cfg(feature = "wkt")]
+pub mod wkt;
+#[cfg(feature = "geojson")]
+pub mod geojson;
+#[cfg(feature = "dxf")]
+pub mod dxf;
+mod validate;
+pub use validate::{validate_ring, ValidationReport, Problem, Orientation};
+
+/// The number of intersections after which resolving the raw offset curves gives up with a `CombinatorialExplosionError`.
+const MAX_INTERSECTIONS: usize = 3000;
 
 #[derive(Debug, Clone, Copy)]
 struct Normal<N: Num> {
@@ -54,6 +98,9 @@ struct Segment<N: Num + Copy + NumCast + PartialOrd> {
     p1: Coordinate<N>,
     p1_orig: Coordinate<N>,
     normal: Normal<N>,
+    offset: N,
+    /// the index of the input edge the segment was created from
+    edge: usize,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -67,60 +114,30 @@ fn is_left<N>(p0: Coordinate<N>, p1: Coordinate<N>, p2: Coordinate<N>) -> N
     (p1.x - p0.x) * (p2.y - p0.y) - (p2.x - p0.x) * (p1.y - p0.y)
 }
 
-// based on http://geomalgorithms.com/a03-_inclusion.html
-fn winding_number<N>(pt: Coordinate<N>, polygon: &Vec<Coordinate<N>>) -> isize
-        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
-    let mut wn = 0;
-    let epsilon = N::from_f32(-0.00001).unwrap(); // oh my
-    for idx in 0..polygon.len()-1 {
-        let p0 = polygon[idx];
-        let p1 = polygon[idx+1];
-        if p0.y <= pt.y { // start y <= point.y
-            if p1.y > pt.y { // an upwards crossing
-                if is_left(p0, p1, pt) >= epsilon { // point left of edge
-                    wn += 1; // have a valid up intersect
-                }
-            }
-        } else { // start y > point.y
-            if p1.y <= pt.y { // a downward crossing
-                if is_left(p0, p1, pt) < epsilon { // point right of edge
-                    wn -= 1;
-                }
-            }
-        }
+/// Returns the unit normal of the segment (pointing to the right of its direction, so outwards for counter-clockwise polygons)
+/// and its length, or `None` if the segment is degenerate.
+fn segment_normal<N>(p0: Coordinate<N>, p1: Coordinate<N>) -> Option<(Normal<N>, N)>
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let len = ((p0.x - p1.x)*(p0.x - p1.x) + (p0.y - p1.y)*(p0.y - p1.y)).sqrt();
+    if len < N::epsilon() {
+        None
+    } else {
+        Some((Normal {
+            x: (p1.y - p0.y) / len,
+            y: (p0.x - p1.x) / len,
+        }, len))
     }
-    wn
 }
 
-/// The core function of this crate. Expands or shrinks the given polygon by the offset. It support `f32` and `f64` for its calculations and input/output.
-///
-/// # Arguments
-///
-/// * `polygon` - A polygon to shrink or expand. It has to be closed (the last coordinate has to be the same as the first coordinate)
-/// * `offset` - A positive number expands the polygon, a negative number shrinks it.
-/// * `arcdetail` - Defines how many points should be added in a sharp corner. This number is the number of vertices inserted if it's a full circle. The actual number inserted depends on the angle of the corner.
-///
-/// Returns a vector of polygons (if the polygon is shrunk more than its thinnest section, multiple polygons will be generated).
-/// The error occurs when there are too many intersections during the operation and should never happen. It's there to avoid infinite loops
-/// (which the author did experience with certain edge cases).
-pub fn offset_polygon<N>(polygon: &LineString<N>, offset: N, arcdetail: N) -> Result<Vec<LineString<N>>, CombinatorialExplosionError>
-        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
-    if polygon.0.len() == 0 {
-        return Ok(vec![LineString(Vec::new())]);
-    }
-    let arcstep = N::from_f32(2.0).unwrap() * N::PI() / arcdetail;
-
-    let lines: Vec<Segment<N>> = (0..(polygon.0.len()-1)).map(|idx| {
+/// Offsets the edges of the polygon, each by the distance returned by `offset` for its edge index. Degenerate edges are skipped.
+fn offset_segments<N, F>(polygon: &LineString<N>, offset: F) -> Vec<Segment<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign,
+              F: Fn(usize) -> N {
+    (0..(polygon.0.len()-1)).filter_map(|idx| {
         let (p0, p1) = (polygon.0[idx], polygon.0[idx+1]);
-        let len = ((p0.x - p1.x)*(p0.x - p1.x) + (p0.y - p1.y)*(p0.y - p1.y)).sqrt();
-        if len < N::epsilon() {
-            None
-        } else {
-            let normal = Normal {
-                x: (p1.y - p0.y) / len,
-                y: (p0.x - p1.x) / len,
-            };
-            Some(Segment {
+        segment_normal(p0, p1).map(|(normal, _)| {
+            let offset = offset(idx);
+            Segment {
                 p0: Coordinate {
                     x: p0.x + offset * normal.x,
                     y: p0.y + offset * normal.y,
@@ -131,156 +148,543 @@ pub fn offset_polygon<N>(polygon: &LineString<N>, offset: N, arcdetail: N) -> Re
                 },
                 p1_orig: p1,
                 normal,
-            })
+                offset,
+                edge: idx,
+            }
+        })
+    }).collect()
+}
+
+/// Builds the raw offset curve by joining the offset segments. Adjacent segments with the same offset are joined by an arc
+/// (or via the original vertex), segments with different offsets are trimmed or extended to meet each other.
+fn connect<N>(lines: Vec<Segment<N>>, arcstep: N) -> Vec<Coordinate<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    connect_traced(lines, arcstep).0
+}
+
+/// Like `connect`, but also returns where each point of the raw offset curve comes from.
+fn connect_traced<N>(mut lines: Vec<Segment<N>>, arcstep: N) -> (Vec<Coordinate<N>>, Vec<Origin>)
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    for idx in 0..lines.len() {
+        let next = (idx+1) % lines.len();
+        let (line0, line1) = (&lines[idx], &lines[next]);
+        if line0.offset == line1.offset {
+            continue;
         }
-    }).flatten().collect();
+        // the direction of a segment is its normal rotated counter-clockwise
+        let (d0, d1) = (Normal { x: -line0.normal.y, y: line0.normal.x }, Normal { x: -line1.normal.y, y: line1.normal.x });
+        let denominator = d0.x * d1.y - d0.y * d1.x;
+        if denominator.abs() < N::epsilon() { // parallel, the gap is closed by connecting the end points directly
+            continue;
+        }
+        let s = ((line1.p0.x - line0.p1.x) * d1.y - (line1.p0.y - line0.p1.y) * d1.x) / denominator;
+        let corner = Coordinate {
+            x: line0.p1.x + s * d0.x,
+            y: line0.p1.y + s * d0.y,
+        };
+        lines[idx].p1 = corner;
+        lines[next].p0 = corner;
+    }
 
     let mut connected = Vec::new();
+    let mut origins = Vec::new();
 
     for idx in 0..lines.len() {
         let (line0, line1) = (&lines[idx], &lines[(idx+1) % lines.len()]);
         connected.extend_from_slice(&[line0.p0, line0.p1]);
-        let startangle = line0.normal.y.atan2(line0.normal.x);
-        let mut endangle = line1.normal.y.atan2(line1.normal.x);
-        let mut angle = startangle - endangle;
-        if angle.is_sign_negative() {
-            angle += N::from_f32(2.0).unwrap() * N::PI();
-        }
-        if offset.is_sign_negative() {
-            angle = N::from_f32(2.0).unwrap() * N::PI() - angle;
-        }
-        if angle < N::PI() { // normals facing outwards
-            if angle > N::epsilon() {
-                connected.push(line0.p1_orig);
-            } else { // lines are facing the same direction, remove one of the two coincident points
+        origins.resize(connected.len(), Origin::Edge(line0.edge));
+        if line0.offset != line1.offset {
+            if line0.p1 == line1.p0 { // trimmed to a common corner above
                 connected.pop();
             }
-        } else if angle > N::PI() { // normals facing inwards, add arc
-            if offset.is_sign_negative() {
-                if endangle > startangle {
-                    endangle -= N::from_f32(2.0).unwrap() * N::PI();
-                }
-                for step in 1..<usize as NumCast>::from(((startangle - endangle)/arcstep).ceil()).unwrap() {
-                    let angle = startangle - N::from(step).unwrap() * arcstep;
-                    connected.push(Coordinate {
-                        x: line0.p1_orig.x + offset * angle.cos(),
-                        y: line0.p1_orig.y + offset * angle.sin(),
-                    });
-                }
-            } else {
-                if endangle < startangle {
-                    endangle += N::from_f32(2.0).unwrap() * N::PI();
-                }
-                for step in 1..<usize as NumCast>::from(((endangle - startangle)/arcstep).ceil()).unwrap() {
-                    let angle = startangle + N::from(step).unwrap() * arcstep;
-                    connected.push(Coordinate {
-                        x: line0.p1_orig.x + offset * angle.cos(),
-                        y: line0.p1_orig.y + offset * angle.sin(),
-                    });
-                }
-            }
+            origins.truncate(connected.len());
+            continue;
+        }
+        let offset = line0.offset;
+        if offset == N::zero() { // untouched edges meet at the original vertex already
+            connected.pop();
+            origins.truncate(connected.len());
+            continue;
         }
+        join(&mut connected, line0, line1, offset, arcstep);
+        origins.truncate(connected.len());
+        origins.resize(connected.len(), Origin::Corner(line0.edge + 1));
     }
 
     connected.push(connected[0]);
+    origins.push(origins[0]);
+    (connected, origins)
+}
 
-    // find intersections and add them to the indices array
-    let mut intersections: Vec<Coordinate<N>> = Vec::new();
-    let lookup = |idx: Index, intersections: &Vec<Coordinate<N>>, connected: &Vec<Coordinate<N>>| {
-        match idx {
-            Index::Intersection(idx) => intersections[idx],
-            Index::Connected(idx) => connected[idx],
+/// Joins two consecutive offset segments with the same offset at their common vertex, either with an arc or via the original vertex.
+fn join<N>(connected: &mut Vec<Coordinate<N>>, line0: &Segment<N>, line1: &Segment<N>, offset: N, arcstep: N)
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    join_with(connected, line0, line1, offset.is_sign_negative(), arcstep, |angle| Coordinate {
+        x: offset * angle.cos(),
+        y: offset * angle.sin(),
+    });
+}
+
+/// Like `join`, but the points of the arc are given by `displacement`, which maps the angle of a normal to the displacement of the vertex.
+/// `inwards` has to be set when the polygon is shrunk.
+fn join_with<N, F>(connected: &mut Vec<Coordinate<N>>, line0: &Segment<N>, line1: &Segment<N>, inwards: bool, arcstep: N, displacement: F)
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign,
+              F: Fn(N) -> Coordinate<N> {
+    let startangle = line0.normal.y.atan2(line0.normal.x);
+    let endangle = line1.normal.y.atan2(line1.normal.x);
+    let mut angle = startangle - endangle;
+    if angle.is_sign_negative() {
+        angle += N::from_f32(2.0).unwrap() * N::PI();
+    }
+    if inwards {
+        angle = N::from_f32(2.0).unwrap() * N::PI() - angle;
+    }
+    if angle < N::PI() { // normals facing outwards
+        if angle > N::epsilon() {
+            connected.push(line0.p1_orig);
+        } else { // lines are facing the same direction, remove one of the two coincident points
+            connected.pop();
         }
-    };
-    let mut indices: Vec<Index> = (0..(connected.len()-1)).map(|idx| Index::Connected(idx)).collect();
-    let mut indices_idx = 0;
-    while indices_idx < indices.len()-1 {
-        let mut p0 = lookup(indices[indices_idx], &intersections, &connected);
-        let p1 = lookup(indices[(indices_idx+1) % indices.len()], &intersections, &connected);
+    } else if angle > N::PI() { // normals facing inwards, add arc
+        arc_with(connected, line0.p1_orig, inwards, startangle, endangle, arcstep, displacement);
+    }
+}
 
-        loop {
-            // exclude line itself from intersection test
-            let mut rest = Vec::new();
-            if indices_idx+2 < indices.len() {
-                rest.extend_from_slice(&indices[(indices_idx+2)..]);
-                rest.extend_from_slice(&indices[0..indices_idx]);
-            } else {
-                rest.extend_from_slice(&indices[((indices_idx+2) % indices.len())..indices_idx]);
-            }
-            let rest_points = rest.into_iter().map(|idx| lookup(idx, &intersections, &connected)).collect();
-            if let Some(int) = intersect(p0, p1, &rest_points, true) {
-                intersections.push(int.point);
-                if intersections.len() > 3000 {
+/// Adds the points of an arc around `center` between the two angles (both exclusive). The arc runs clockwise for negative offsets.
+fn arc<N>(connected: &mut Vec<Coordinate<N>>, center: Coordinate<N>, offset: N, startangle: N, endangle: N, arcstep: N)
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    arc_with(connected, center, offset.is_sign_negative(), startangle, endangle, arcstep, |angle| Coordinate {
+        x: offset * angle.cos(),
+        y: offset * angle.sin(),
+    });
+}
+
+/// Like `arc`, but the points are given by `displacement` (see `join_with`). The arc runs clockwise if `inwards` is set.
+fn arc_with<N, F>(connected: &mut Vec<Coordinate<N>>, center: Coordinate<N>, inwards: bool, startangle: N, mut endangle: N, arcstep: N, displacement: F)
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign,
+              F: Fn(N) -> Coordinate<N> {
+    if inwards {
+        if endangle > startangle {
+            endangle -= N::from_f32(2.0).unwrap() * N::PI();
+        }
+        for step in 1..<usize as NumCast>::from(((startangle - endangle)/arcstep).ceil()).unwrap() {
+            let displacement = displacement(startangle - N::from(step).unwrap() * arcstep);
+            connected.push(Coordinate {
+                x: center.x + displacement.x,
+                y: center.y + displacement.y,
+            });
+        }
+    } else {
+        if endangle < startangle {
+            endangle += N::from_f32(2.0).unwrap() * N::PI();
+        }
+        for step in 1..<usize as NumCast>::from(((endangle - startangle)/arcstep).ceil()).unwrap() {
+            let displacement = displacement(startangle + N::from(step).unwrap() * arcstep);
+            connected.push(Coordinate {
+                x: center.x + displacement.x,
+                y: center.y + displacement.y,
+            });
+        }
+    }
+}
+
+/// Finds all intersections of the raw offset curves with themselves and each other and inserts them into the index lists of the
+/// curves (twice, once for each segment involved). On an error, the index lists contain all intersections found until then.
+fn split_at_intersections<N>(connected: &[Coordinate<N>], cycles: &mut [Vec<Index>], intersections: &mut Vec<Coordinate<N>>) -> Result<(), CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    for cycle in 0..cycles.len() {
+        let mut indices_idx = 0;
+        // the closing segment only has to be tested against the other curves, its intersections with the curve itself are found
+        // by the other segments
+        while indices_idx < cycles[cycle].len() - if cycles.len() > 1 { 0 } else { 1 } {
+            let indices = &cycles[cycle];
+            let mut p0 = lookup(indices[indices_idx], intersections, connected);
+            let p1 = lookup(indices[(indices_idx+1) % indices.len()], intersections, connected);
+
+            loop {
+                let indices = &cycles[cycle];
+                let mut nearest = None;
+                if indices_idx < indices.len()-1 {
+                    // exclude line itself from intersection test
+                    let mut rest = Vec::new();
+                    if indices_idx+2 < indices.len() {
+                        rest.extend_from_slice(&indices[(indices_idx+2)..]);
+                        rest.extend_from_slice(&indices[0..indices_idx]);
+                    } else {
+                        rest.extend_from_slice(&indices[((indices_idx+2) % indices.len())..indices_idx]);
+                    }
+                    let rest_points = rest.into_iter().map(|idx| lookup(idx, intersections, connected)).collect();
+                    nearest = intersect(p0, p1, &rest_points, true).map(|int| (int, cycle));
+                }
+                for (other, other_indices) in cycles.iter().enumerate().filter(|(other, _)| *other != cycle) {
+                    let points = other_indices.iter().chain(other_indices.first()).map(|idx| lookup(*idx, intersections, connected)).collect();
+                    if let Some(int) = intersect(p0, p1, &points, true) {
+                        if nearest.map_or(true, |(nearest, _)| int.u < nearest.u) {
+                            nearest = Some((int, other));
+                        }
+                    }
+                }
+                let (int, other) = match nearest {
+                    Some(nearest) => nearest,
+                    None => break,
+                };
+                if intersections.len() >= MAX_INTERSECTIONS {
                     return Err(error::CombinatorialExplosionError);
                 }
+                intersections.push(int.point);
+                let indices = &mut cycles[cycle];
                 indices.insert(indices_idx+1, Index::Intersection(intersections.len()-1));
-                let mut other_indices_idx = (indices_idx+3+int.index) % indices.len();
-                if other_indices_idx > indices_idx {
-                    other_indices_idx += 1;
+                if other == cycle {
+                    let mut other_indices_idx = (indices_idx+3+int.index) % indices.len();
+                    if other_indices_idx > indices_idx {
+                        other_indices_idx += 1;
+                    }
+                    indices.insert(other_indices_idx, Index::Intersection(intersections.len()-1));
+                } else {
+                    cycles[other].insert(int.index+1, Index::Intersection(intersections.len()-1));
                 }
-                indices.insert(other_indices_idx, Index::Intersection(intersections.len()-1));
                 p0 = int.point;
                 indices_idx += 1;
-            } else {
-                break;
             }
+            indices_idx += 1;
         }
-        indices_idx += 1;
     }
+    Ok(())
+}
+
+fn lookup<N>(idx: Index, intersections: &[Coordinate<N>], connected: &[Coordinate<N>]) -> Coordinate<N>
+        where N: Num + Copy + NumCast + PartialOrd {
+    match idx {
+        Index::Intersection(idx) => intersections[idx],
+        Index::Connected(idx) => connected[idx],
+    }
+}
 
-    // find all regions in this polygon
+/// Walks along the index lists, switching over to the other segment at every intersection. This splits the curves into closed regions.
+fn trace_regions(cycles: &[Vec<Index>]) -> Vec<Vec<Index>> {
     let mut regions = Vec::new();
-    let mut remaining: Vec<usize> = (0..indices.len()).collect();
-    while remaining.len() > 0 {
-        let mut indices_idx = remaining[0];
+    let mut remaining: Vec<(usize, usize)> = cycles.iter().enumerate().flat_map(|(cycle, indices)| (0..indices.len()).map(move |indices_idx| (cycle, indices_idx))).collect();
+    while !remaining.is_empty() {
+        let (mut cycle, mut indices_idx) = remaining[0];
 
         let mut current_region = Vec::new();
-        let start_idx = indices_idx;
+        let start = (cycle, indices_idx);
         loop {
-            let idx = indices[indices_idx];
-            if let Some(remaining_idx) = remaining.iter().position(|idx| *idx == indices_idx) {
+            let idx = cycles[cycle][indices_idx];
+            if let Some(remaining_idx) = remaining.iter().position(|position| *position == (cycle, indices_idx)) {
                 remaining.remove(remaining_idx);
             }
+            current_region.push(idx);
             match idx {
-                Index::Intersection(idx_num) => {
-                    current_region.push(intersections[idx_num]);
-                    indices_idx = indices.iter().skip(indices_idx+1).position(|i| *i == idx).map(|i| i + indices_idx + 1).unwrap_or_else(|| {
+                Index::Intersection(_) => {
+                    let indices = &cycles[cycle];
+                    let other = indices.iter().skip(indices_idx+1).position(|i| *i == idx).map(|i| (cycle, i + indices_idx + 1)).or_else(|| {
+                        // matching entry is on another curve
+                        cycles.iter().enumerate().filter(|(other, _)| *other != cycle)
+                            .find_map(|(other, indices)| indices.iter().position(|i| *i == idx).map(|i| (other, i)))
+                    }).unwrap_or_else(|| {
                          // matching entry is before our entry in the array
-                        indices.iter().position(|i| *i == idx).unwrap()
+                        (cycle, indices.iter().position(|i| *i == idx).unwrap())
                     });
+                    cycle = other.0;
+                    indices_idx = other.1;
                 },
-                Index::Connected(idx_num) => {
-                    current_region.push(connected[idx_num]);
-                },
+                Index::Connected(_) => {},
             }
-            indices_idx = (indices_idx+1) % indices.len();
-            if start_idx == indices_idx {
+            indices_idx = (indices_idx+1) % cycles[cycle].len();
+            if start == (cycle, indices_idx) {
                 break;
             }
         }
-        if current_region.len() > 0 {
+        if !current_region.is_empty() {
             current_region.push(current_region[0]); // line string has to be closed
             regions.push(current_region);
         }
     }
+    regions
+}
+
+/// The regions of the raw offset curves as indices together with the winding number of the area they enclose, the split curves
+/// and the intersections the indices refer to.
+type SplitRegions<N> = (Vec<(Vec<Index>, isize)>, Vec<Vec<Index>>, Vec<Coordinate<N>>);
+
+/// Resolves the intersections of the raw offset curves and keeps the regions that separate an area filled by the fill rule from
+/// one that isn't. Each curve is given by the range of its points in `connected`, the first point of a curve has to be repeated at
+/// its end. The winding number on the left of a region is the sum of the ones of all curves at its probe point, the one on its right
+/// is lower by how often the region passes the probe point in the direction of its probe segment. Regions that are filled on their
+/// right are reversed, so that the filled area is always on their left. The regions are returned as indices into the split curves,
+/// which are returned as well, together with the intersections. If a trace is given, all intermediate stages are recorded in it, on
+/// an error until the point where it occurred.
+fn extract_region_indices<N>(connected: &[Coordinate<N>], curves: &[Range<usize>], fill_rule: FillRule, mut trace: Option<&mut OffsetTrace<N>>) -> Result<SplitRegions<N>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let mut cycles: Vec<Vec<Index>> = curves.iter().map(|curve| (curve.start..(curve.end-1)).map(Index::Connected).collect()).collect();
+    let mut intersections: Vec<Coordinate<N>> = Vec::new();
+    let result = split_at_intersections(connected, &mut cycles, &mut intersections);
+    if let Some(trace) = trace.as_mut() {
+        trace.raw_curve = LineString(connected.to_vec());
+        trace.intersections = intersection_segments(&cycles.concat(), intersections.len()).into_iter().zip(intersections.iter())
+            .map(|(segments, point)| TraceIntersection {
+                point: *point,
+                segments: (segments[0], segments[segments.len()-1]),
+            }).collect();
+    }
+    result?;
+
+    // find all regions in these polygons
+    let regions = trace_regions(&cycles);
+
+    let curves: Vec<Vec<Coordinate<N>>> = cycles.iter().map(|indices| {
+        let mut curve: Vec<Coordinate<N>> = indices.iter().map(|idx| lookup(*idx, &intersections, connected)).collect();
+        curve.push(curve[0]); // line string has to be closed
+        curve
+    }).collect();
 
-    connected = indices.into_iter().map(|idx| lookup(idx, &intersections, &connected)).collect();
-    connected.push(connected[0]); // line string has to be closed
+    let regions = regions.into_iter().filter_map(|mut region| {
+        let ring: Vec<Coordinate<N>> = region.iter().map(|idx| lookup(*idx, &intersections, connected)).collect();
+        let probe = region_probe(&ring).map(|(p0, p1)| {
+            let probe = Coordinate {
+                x: (p0.x + p1.x) * N::from_f32(0.5).unwrap(),
+                y: (p0.y + p1.y) * N::from_f32(0.5).unwrap(),
+            };
+            let left = curves.iter().map(|curve| winding_number(probe, curve)).sum::<isize>();
+            (probe, left, left - passes(probe, p1.y - p0.y, &ring))
+        });
+        let filled = probe.and_then(|(_, left, right)| fill_rule.filled_side(left, right));
+        if let Some(trace) = trace.as_mut() {
+            trace.regions.push(TraceRegion {
+                ring: LineString(ring),
+                probe: probe.map(|(probe, left, _)| (probe, left)),
+                kept: filled.is_some(),
+            });
+        }
+        let (winding_number, (_, left, _)) = (filled?, probe?);
+        if winding_number != left {
+            region.reverse();
+        }
+        Some((region, winding_number))
+    }).collect();
+    Ok((regions, cycles, intersections))
+}
 
+/// Returns the segment of the closed ring where the winding number of its region is calculated, its first segment that isn't
+/// (almost) horizontal. Regions without such a segment don't have one and are never kept.
+fn region_probe<N>(ring: &[Coordinate<N>]) -> Option<(Coordinate<N>, Coordinate<N>)>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
     let epsilon = N::from_f32(0.01).unwrap();
-    Ok(regions.into_iter().filter(|region| {
-        for idx in 0..(region.len()-1) {
-            let p0 = region[idx];
-            let p1 = region[idx+1];
-            if (p1.y - p0.y).abs() > epsilon {
-                return winding_number(Coordinate { // center point
-                    x: (p0.x + p1.x) * N::from_f32(0.5).unwrap(),
-                    y: (p0.y + p1.y) * N::from_f32(0.5).unwrap(),
-                }, &connected) == 1;
+    ring.windows(2).map(|pair| (pair[0], pair[1])).find(|(p0, p1)| (p1.y - p0.y).abs() > epsilon)
+}
+
+/// Returns how often the closed ring passes the point upwards (if `dy` is positive) or downwards (if it's negative), minus how
+/// often it passes it in the other direction. Uses the same tolerance as the winding number.
+fn passes<N>(pt: Coordinate<N>, dy: N, ring: &[Coordinate<N>]) -> isize
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    let epsilon = N::from_f32(0.00001).unwrap();
+    ring.windows(2).filter(|pair| (pair[0].y <= pt.y) != (pair[1].y <= pt.y) && is_left(pair[0], pair[1], pt).abs() < epsilon)
+        .map(|pair| if (pair[1].y > pair[0].y) == (dy > N::zero()) { 1 } else { -1 })
+        .sum()
+}
+
+/// Returns the segments of the raw offset curve each intersection is on, segment `i` runs from point `i` to point `i+1`. Every
+/// intersection is in the index list twice, each time after the segment of the raw offset curve it is on.
+fn intersection_segments(indices: &[Index], count: usize) -> Vec<Vec<usize>> {
+    let mut segments = vec![Vec::new(); count];
+    let mut segment = indices.iter().rev().find_map(|idx| match idx {
+        Index::Connected(segment) => Some(*segment),
+        Index::Intersection(_) => None,
+    }).unwrap_or(0);
+    for idx in indices.iter() {
+        match idx {
+            Index::Connected(idx) => segment = *idx,
+            Index::Intersection(intersection) => segments[*intersection].push(segment),
+        }
+    }
+    segments
+}
+
+/// Resolves the self-intersections of the raw offset curve and keeps the regions with a winding number of 1 (the boundaries of the
+/// positive winding numbers).
+fn extract_regions<N>(connected: Vec<Coordinate<N>>) -> Result<Vec<LineString<N>>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    Ok(extract_filled_regions(vec![connected], FillRule::Positive, None)?.into_iter().map(|(region, _)| region).collect())
+}
+
+/// Resolves the intersections of the closed raw offset curves and keeps the boundaries of the areas filled by the fill rule, each
+/// with the winding number of the area. Empty curves are skipped. See `extract_region_indices` for the trace.
+fn extract_filled_regions<N>(curves: Vec<Vec<Coordinate<N>>>, fill_rule: FillRule, trace: Option<&mut OffsetTrace<N>>) -> Result<Vec<(LineString<N>, isize)>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let mut connected = Vec::new();
+    let mut ranges = Vec::new();
+    for curve in curves.into_iter().filter(|curve| !curve.is_empty()) {
+        ranges.push(connected.len()..(connected.len() + curve.len()));
+        connected.extend(curve);
+    }
+    if ranges.is_empty() {
+        return Ok(Vec::new());
+    }
+    let (regions, _, intersections) = extract_region_indices(&connected, &ranges, fill_rule, trace)?;
+    Ok(regions.into_iter().map(|(region, winding_number)| {
+        (LineString(region.into_iter().map(|idx| lookup(idx, &intersections, &connected)).collect()), winding_number)
+    }).collect())
+}
+
+/// Returns the signed area of the ring, positive for counter-clockwise rings. The ring may be closed or not.
+fn signed_area<N>(ring: &[Coordinate<N>]) -> N
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    (0..ring.len()).fold(N::zero(), |area, idx| {
+        let (p0, p1) = (ring[idx], ring[(idx+1) % ring.len()]);
+        area + p0.x * p1.y - p1.x * p0.y
+    }) * N::from_f32(0.5).unwrap()
+}
+
+/// Returns whether the point is inside of the closed ring or on its boundary.
+fn contains<N>(ring: &[Coordinate<N>], point: Coordinate<N>) -> bool
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let mut wn = 0;
+    for idx in 0..ring.len()-1 {
+        let (p0, p1) = (ring[idx], ring[idx+1]);
+        let side = is_left(p0, p1, point);
+        if side == N::zero() && (point.x - p0.x) * (point.x - p1.x) <= N::zero() && (point.y - p0.y) * (point.y - p1.y) <= N::zero() {
+            return true;
+        }
+        if p0.y <= point.y {
+            if p1.y > point.y && side > N::zero() {
+                wn += 1;
             }
+        } else if p1.y <= point.y && side < N::zero() {
+            wn -= 1;
+        }
+    }
+    wn != 0
+}
+
+/// Assembles closed rings into polygons. Counter-clockwise rings are exteriors, clockwise rings are holes, which are assigned to
+/// the smallest exterior around them.
+fn assemble_polygons<N>(rings: Vec<Vec<Coordinate<N>>>) -> Vec<Polygon<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    let (shells, holes): (Vec<_>, Vec<_>) = rings.into_iter().partition(|ring| signed_area(ring) > N::zero());
+    let mut interiors: Vec<Vec<LineString<N>>> = vec![Vec::new(); shells.len()];
+    for hole in holes {
+        // the hole is inside of its shell, so the center of any of its edges is inside as well (or on the boundary if they touch)
+        let center = Coordinate {
+            x: (hole[0].x + hole[1].x) * N::from_f32(0.5).unwrap(),
+            y: (hole[0].y + hole[1].y) * N::from_f32(0.5).unwrap(),
+        };
+        let shell = (0..shells.len()).filter(|idx| contains(&shells[*idx], center))
+            .min_by(|a, b| signed_area(&shells[*a]).partial_cmp(&signed_area(&shells[*b])).unwrap());
+        if let Some(shell) = shell {
+            interiors[shell].push(LineString(hole));
         }
-        false
-    }).map(|coordinates| LineString(coordinates)).collect())
+    }
+    shells.into_iter().zip(interiors).map(|(shell, interiors)| Polygon::new(LineString(shell), interiors)).collect()
+}
+
+/// Returns the ring closed and oriented counter-clockwise (or clockwise for holes).
+fn oriented_ring<N>(ring: &LineString<N>, hole: bool) -> LineString<N>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    let mut ring = ring.clone();
+    if ring.0.len() > 1 && ring.0.first() != ring.0.last() {
+        ring.0.push(ring.0[0]);
+    }
+    if signed_area(&ring.0).is_sign_negative() != hole {
+        ring.0.reverse();
+    }
+    ring
+}
+
+/// Builds the raw offset curve of a ring after orienting it counter-clockwise (or clockwise for holes), so that a positive
+/// offset always moves it away from the area it encloses.
+fn raw_offset_curve<N>(ring: &LineString<N>, offset: N, arcstep: N, hole: bool) -> Vec<Coordinate<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    if ring.0.len() < 2 {
+        return Vec::new();
+    }
+    let lines = offset_segments(&oriented_ring(ring, hole), |_| offset);
+    if lines.len() < 2 {
+        return Vec::new();
+    }
+    connect(lines, arcstep)
+}
+
+/// Offsets polygons with holes. The raw offset curves of all rings are resolved together, so that overlapping parts are merged
+/// and holes are kept where they don't vanish.
+fn offset_polygons<N>(polygons: &MultiPolygon<N>, offset: N, arcstep: N) -> Result<MultiPolygon<N>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let curves: Vec<Vec<Coordinate<N>>> = polygons.0.iter().flat_map(|polygon| {
+        std::iter::once(raw_offset_curve(polygon.exterior(), offset, arcstep, false))
+            .chain(polygon.interiors().iter().map(|interior| raw_offset_curve(interior, offset, arcstep, true)))
+            .collect::<Vec<_>>()
+    }).collect();
+    let regions = extract_filled_regions(curves, FillRule::Positive, None)?;
+    Ok(MultiPolygon(assemble_polygons(regions.into_iter().map(|(region, _)| region.0).collect())))
+}
+
+/// The core function of this crate. Expands or shrinks the given polygon by the offset. It support `f32` and `f64` for its calculations and input/output.
+///
+/// # Arguments
+///
+/// * `polygon` - A polygon to shrink or expand. It has to be closed (the last coordinate has to be the same as the first coordinate)
+/// * `offset` - A positive number expands the polygon, a negative number shrinks it.
+/// * `arcdetail` - Defines how many points should be added in a sharp corner. This number is the number of vertices inserted if it's a full circle. The actual number inserted depends on the angle of the corner.
+///
+/// Returns a vector of polygons (if the polygon is shrunk more than its thinnest section, multiple polygons will be generated).
+/// The error occurs when there are too many intersections during the operation and should never happen. It's there to avoid infinite loops
+/// (which the author did experience with certain edge cases).
+pub fn offset_polygon<N>(polygon: &LineString<N>, offset: N, arcdetail: N) -> Result<Vec<LineString<N>>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    if polygon.0.is_empty() {
+        return Ok(vec![LineString(Vec::new())]);
+    }
+    let arcstep = N::from_f32(2.0).unwrap() * N::PI() / arcdetail;
+
+    let lines = offset_segments(polygon, |_| offset);
+    extract_regions(connect(lines, arcstep))
+}
+
+/// Like [`offset_polygon`](fn.offset_polygon.html), but only moves a subset of the polygon's edges. The other edges stay on their
+/// original geometry and are extended or trimmed to meet the offset edges next to them.
+///
+/// # Arguments
+///
+/// * `polygon` - A polygon to shrink or expand. It has to be closed (the last coordinate has to be the same as the first coordinate)
+/// * `offset` - A positive number expands the polygon, a negative number shrinks it.
+/// * `arcdetail` - Defines how many points should be added in a sharp corner between two offset edges.
+/// * `mask` - One entry per edge (edge `i` goes from coordinate `i` to coordinate `i+1`), `true` if the edge should be offset. Missing entries count as `false`.
+pub fn offset_polygon_partial<N>(polygon: &LineString<N>, offset: N, arcdetail: N, mask: &[bool]) -> Result<Vec<LineString<N>>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    if polygon.0.is_empty() {
+        return Ok(vec![LineString(Vec::new())]);
+    }
+    let arcstep = N::from_f32(2.0).unwrap() * N::PI() / arcdetail;
+
+    let lines = offset_segments(polygon, |idx| if mask.get(idx).cloned().unwrap_or(false) { offset } else { N::zero() });
+    extract_regions(connect(lines, arcstep))
+}
+
+/// Like [`offset_polygon`](fn.offset_polygon.html), but selects the regions of the raw offset curve by the given fill rule. This
+/// gives control over the result for input that overlaps itself, like a figure-eight or a ring that winds around the same area twice.
+/// A region is kept if the area on one of its sides is filled and the one on the other side isn't. `offset_polygon` is this function
+/// with [`FillRule::Positive`](enum.FillRule.html), which keeps the regions with a winding number of 1 on their left and 0 on their
+/// right. The loops the raw offset curve forms in the corners of a shrunk polygon don't enclose a positive winding number and are dropped.
+///
+/// # Arguments
+///
+/// * `polygon` - A polygon to shrink or expand. It has to be closed (the last coordinate has to be the same as the first coordinate)
+/// * `offset` - A positive number expands the polygon, a negative number shrinks it.
+/// * `arcdetail` - Defines how many points should be added in a sharp corner. This number is the number of vertices inserted if it's a full circle.
+/// * `fill_rule` - Selects the regions by their winding number.
+///
+/// Returns the selected regions together with the winding number of the area they enclose. Adjacent regions with different
+/// winding numbers are returned separately. Every region is oriented so that the filled area is on its left. The error is the
+/// same as the one of `offset_polygon`.
+pub fn offset_polygon_with_fill_rule<N>(polygon: &LineString<N>, offset: N, arcdetail: N, fill_rule: FillRule) -> Result<Vec<(LineString<N>, isize)>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    if polygon.0.is_empty() {
+        return Ok(Vec::new());
+    }
+    let arcstep = N::from_f32(2.0).unwrap() * N::PI() / arcdetail;
+
+    let lines = offset_segments(polygon, |_| offset);
+    extract_filled_regions(vec![connect(lines, arcstep)], fill_rule, None)
 }
 
 #[cfg(test)]
@@ -370,6 +774,34 @@ mod tests {
         assert!(result.len() == 1, "Complex input should result in one polygon");
     }
     #[test]
+    fn partial_contract() {
+        let input = LineString(vec![
+            Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 10.0, y: 0.0 }, Coordinate { x: 10.0, y: 10.0 }, Coordinate { x: 0.0, y: 10.0 }, Coordinate { x: 0.0, y: 0.0 },
+        ]);
+        let result = offset_polygon_partial(&input, -1.0, 10.0, &[true, false, false, false]).unwrap();
+        assert!(result.len() == 1, "Partial offsetting of a rectangle should result in one polygon");
+        let first = result.first().unwrap();
+        assert!(first.0.iter().zip(vec![
+            Coordinate { x: 0.0, y: 1.0 }, Coordinate { x: 10.0, y: 1.0 }, Coordinate { x: 10.0, y: 10.0 }, Coordinate { x: 0.0, y: 10.0 }, Coordinate { x: 0.0, y: 1.0 },
+        ]).all(|(p0, p1)| (p0.x - p1.x).abs() < f64::epsilon() && (p0.y - p1.y).abs() < f64::epsilon()), "Incorrect partial offsetting for rectangle");
+    }
+    #[test]
+    fn partial_extend() {
+        let input = LineString(vec![
+            Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 10.0, y: 0.0 }, Coordinate { x: 10.0, y: 10.0 }, Coordinate { x: 0.0, y: 10.0 }, Coordinate { x: 0.0, y: 0.0 },
+        ]);
+        let result = offset_polygon_partial(&input, 1.0, 10.0, &[true, true, false, false]).unwrap();
+        assert!(result.len() == 1, "Partial offsetting of a rectangle should result in one polygon");
+        let first = result.first().unwrap();
+        assert!(first.0.iter().zip(vec![
+            Coordinate { x: 0.0, y: -1.0 }, Coordinate { x: 10.0, y: -1.0 }, Coordinate { x: 10.587785252292473, y: -0.8090169943749475 }, Coordinate { x: 10.951056516295154, y: -0.3090169943749474 }, Coordinate { x: 11.0, y: 0.0 }, Coordinate { x: 11.0, y: 10.0 }, Coordinate { x: 0.0, y: 10.0 }, Coordinate { x: 0.0, y: -1.0 },
+        ]).all(|(p0, p1)| (p0.x - p1.x).abs() < f64::epsilon() && (p0.y - p1.y).abs() < f64::epsilon()), "Incorrect partial offsetting for rectangle");
+
+        let full = offset_polygon(&input, 1.0, 10.0).unwrap();
+        let all = offset_polygon_partial(&input, 1.0, 10.0, &[true; 4]).unwrap();
+        assert!(full == all, "Offsetting all edges should behave like offset_polygon");
+    }
+    #[test]
     fn complex_contract() {
         let input = LineString(vec![
             Coordinate { x: 490.0, y: 210.0 }, Coordinate { x: 1260.0, y: 210.0 }, Coordinate { x: 1260.0, y: 433.2142857142857 }, Coordinate { x: 1260.0, y: 538.2142857142858 }, Coordinate { x: 1260.0, y: 1190.0 }, Coordinate { x: 490.0, y: 1190.0 }, Coordinate { x: 484.2650146484375, y: 1189.7904052734375 }, Coordinate { x: 473.10126876831055, y: 1188.4620895385742 }, Coordinate { x: 462.30468368530273, y: 1186.069725036621 }, Coordinate { x: 451.87491607666016, y: 1182.6432037353516 }, Coordinate { x: 441.8116226196289, y: 1178.212417602539 }, Coordinate { x: 432.1144599914551, y: 1172.807258605957 }, Coordinate { x: 422.78308486938477, y: 1166.457618713379 }, Coordinate { x: 413.81715393066406, y: 1159.1933898925781 }, Coordinate { x: 405.21632385253906, y: 1151.0444641113281 }, Coordinate { x: 396.98025131225586, y: 1142.0407333374023 }, Coordinate { x: 389.10859298706055, y: 1132.2120895385742 }, Coordinate { x: 381.6010055541992, y: 1121.5884246826172 }, Coordinate { x: 374.45714569091797, y: 1110.1996307373047 }, Coordinate { x: 367.6766700744629, y: 1098.0755996704102 }, Coordinate { x: 361.2592353820801, y: 1085.246223449707 }, Coordinate { x: 355.2044982910156, y: 1071.7413940429688 }, Coordinate { x: 349.5121154785156, y: 1057.5910034179688 }, Coordinate { x: 344.1817436218262, y: 1042.8249435424805 }, Coordinate { x: 339.21303939819336, y: 1027.4731063842773 }, Coordinate { x: 334.6056594848633, y: 1011.5653839111328 }, Coordinate { x: 330.35926055908203, y: 995.1316680908203 }, Coordinate { x: 326.4734992980957, y: 978.2018508911133 }, Coordinate { x: 322.9480323791504, y: 960.8058242797852 }, Coordinate { x: 319.7825164794922, y: 942.9734802246094 }, Coordinate { x: 316.9766082763672, y: 924.7347106933594 }, Coordinate { x: 314.5299644470215, y: 906.1194076538086 }, Coordinate { x: 312.4422416687012, y: 887.1574630737305 }, Coordinate { x: 310.71309661865234, y: 867.8787689208984 }, Coordinate { x: 308.7462463378906, y: 838.4624633789062 }, Coordinate { x: 307.435302734375, y: 798.36328125 }, Coordinate { x: 307.552490234375, y: 757.53515625 }, Coordinate { x: 309.0950622558594, y: 716.2172241210938 }, Coordinate { x: 312.0602722167969, y: 674.6486206054688 }, Coordinate { x: 316.44537353515625, y: 633.0684814453125 }, Coordinate { x: 322.24761962890625, y: 591.7159423828125 }, Coordinate { x: 327.5717887878418, y: 561.0149459838867 }, Coordinate { x: 331.53319549560547, y: 540.7335662841797 }, Coordinate { x: 335.8473434448242, y: 520.6436004638672 }, Coordinate { x: 340.51388931274414, y: 500.77494049072266 }, Coordinate { x: 345.5324897766113, y: 481.15747833251953 }, Coordinate { x: 350.9028015136719, y: 461.82110595703125 }, Coordinate { x: 356.6244812011719, y: 442.79571533203125 }, Coordinate { x: 362.6971855163574, y: 424.11119842529297 }, Coordinate { x: 369.1205711364746, y: 405.79744720458984 }, Coordinate { x: 375.89429473876953, y: 387.8843536376953 }, Coordinate { x: 383.0180130004883, y: 370.4018096923828 }, Coordinate { x: 390.49138259887695, y: 353.3797073364258 }, Coordinate { x: 398.31406021118164, y: 336.84793853759766 }, Coordinate { x: 406.48570251464844, y: 320.8363952636719 }, Coordinate { x: 415.00596618652344, y: 305.3749694824219 }, Coordinate { x: 423.87450790405273, y: 290.4935531616211 }, Coordinate { x: 433.0909843444824, y: 276.22203826904297 }, Coordinate { x: 442.6550521850586, y: 262.59031677246094 }, Coordinate { x: 452.56636810302734, y: 249.62828063964844 }, Coordinate { x: 462.82458877563477, y: 237.3658218383789 }, Coordinate { x: 473.42937088012695, y: 225.83283233642578 }, Coordinate { x: 484.38037109375, y: 215.0592041015625 }, Coordinate { x: 490.0, y: 210.0 },
@@ -382,4 +814,36 @@ mod tests {
             Coordinate { x: 1160.0, y: 310.0 }, Coordinate { x: 1160.0, y: 433.2142857142857 }, Coordinate { x: 1160.0, y: 433.2142857142857 }, Coordinate { x: 1160.0, y: 538.2142857142858 }, Coordinate { x: 1160.0, y: 538.2142857142858 }, Coordinate { x: 1160.0, y: 1090.0 }, Coordinate { x: 491.95487015907963, y: 1090.0 }, Coordinate { x: 489.8673026705274, y: 1089.7516112627063 }, Coordinate { x: 488.792910658683, y: 1089.5135417906874 }, Coordinate { x: 487.71385124985136, y: 1089.1590353253052 }, Coordinate { x: 486.40119230265503, y: 1088.5810822838698 }, Coordinate { x: 484.69055973565025, y: 1087.627582650634 }, Coordinate { x: 482.4913505001225, y: 1086.1311058172244 }, Coordinate { x: 479.7809439789146, y: 1083.9351249088265 }, Coordinate { x: 476.5894734387631, y: 1080.9113404219213 }, Coordinate { x: 472.9816205961927, y: 1076.9672110438028 }, Coordinate { x: 469.0401531892465, y: 1072.0458491671611 }, Coordinate { x: 464.8534440537063, y: 1066.1214167042547 }, Coordinate { x: 460.50727992896185, y: 1059.1927298855733 }, Coordinate { x: 456.08024714375534, y: 1051.2768422608544 }, Coordinate { x: 451.64167631735677, y: 1042.403499913998 }, Coordinate { x: 447.25121363610583, y: 1032.610762651393 }, Coordinate { x: 442.9593122991198, y: 1021.9417541811705 }, Coordinate { x: 438.8081688067731, y: 1010.4423625800366 }, Coordinate { x: 434.83281253055617, y: 998.1596789161451 }, Coordinate { x: 431.0621852306548, y: 985.1409798366633 }, Coordinate { x: 427.5201291566048, y: 971.4330960608025 }, Coordinate { x: 424.2262511566025, y: 957.0820465050659 }, Coordinate { x: 421.19665724691407, y: 942.1328504166728 }, Coordinate { x: 418.4445657310514, y: 926.6294556027367 }, Coordinate { x: 415.9808128038696, y: 910.6147400212483 }, Coordinate { x: 413.81426614091515, y: 894.1305578135709 }, Coordinate { x: 411.9521613595333, y: 877.2178105688103 }, Coordinate { x: 410.41462503398714, y: 860.0754126142624 }, Coordinate { x: 408.63716155835095, y: 833.4915850598865 }, Coordinate { x: 407.43999339104306, y: 796.87256937449 }, Coordinate { x: 407.5471343036666, y: 759.5446754164375 }, Coordinate { x: 408.96220794896965, y: 721.6417989090759 }, Coordinate { x: 411.6863450617815, y: 683.4527407794883 }, Coordinate { x: 415.7138273744669, y: 645.2635946529438 }, Coordinate { x: 421.05305239344347, y: 607.2110012962893 }, Coordinate { x: 425.920181500002, y: 579.1454555345098 }, Coordinate { x: 429.4999325520822, y: 560.8180542668297 }, Coordinate { x: 433.41730966003814, y: 542.5757563367458 }, Coordinate { x: 437.63886201023803, y: 524.6017364558829 }, Coordinate { x: 442.1590976388982, y: 506.9323579050607 }, Coordinate { x: 446.9716652774589, y: 489.6042018318771 }, Coordinate { x: 452.06923296316756, y: 472.6540714934276 }, Coordinate { x: 457.44334626524164, y: 456.11898174762257 }, Coordinate { x: 463.0842642879559, y: 440.03612833760354 }, Coordinate { x: 468.98077204596916, y: 424.44282967519194 }, Coordinate { x: 475.11996872673336, y: 409.37643150822095 }, Coordinate { x: 481.48703302157094, y: 394.8741619888528 }, Coordinate { x: 488.0649694914805, y: 380.9729212266266 }, Coordinate { x: 494.8343443429819, y: 367.7089854904458 }, Coordinate { x: 501.77302568536385, y: 355.11760208653595 }, Coordinate { x: 508.85595313056723, y: 343.23244718291085 }, Coordinate { x: 516.0549753547518, y: 332.084916611058 }, Coordinate { x: 523.338812698308, y: 321.7032208986425 }, Coordinate { x: 530.673225187708, y: 312.1112635352212 }, Coordinate { x: 532.439413159739, y: 310.0 }, Coordinate { x: 1160.0, y: 310.0 },
         ]).all(|(p0, p1)| (p0.x - p1.x).abs() < f64::epsilon() && (p0.y - p1.y).abs() < f64::epsilon()), "Incorrect offsetting for complex polygon");
     }
+    #[test]
+    fn fill_rules() {
+        let figure_eight = LineString(vec![
+            Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 4.0, y: 4.0 }, Coordinate { x: 4.0, y: 0.0 }, Coordinate { x: 0.0, y: 4.0 }, Coordinate { x: 0.0, y: 0.0 },
+        ]);
+        let result = offset_polygon_with_fill_rule(&figure_eight, 0.0, 10.0, FillRule::NonZero).unwrap();
+        let mut winding_numbers: Vec<isize> = result.iter().map(|(_, winding_number)| *winding_number).collect();
+        winding_numbers.sort_unstable();
+        assert!(winding_numbers == vec![-1, 1], "Both loops of a figure-eight have to be kept with the nonzero rule");
+        let result = offset_polygon_with_fill_rule(&figure_eight, 0.0, 10.0, FillRule::Negative).unwrap();
+        assert!(result.len() == 1 && result[0].1 == -1 && result[0].0.0.iter().all(|p| p.x >= 2.0), "Only the clockwise loop has a negative winding number");
+
+        // a square traversed twice
+        let twice = LineString(vec![
+            Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 4.0, y: 0.0 }, Coordinate { x: 4.0, y: 4.0 }, Coordinate { x: 0.0, y: 4.0 },
+            Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 4.0, y: 0.0 }, Coordinate { x: 4.0, y: 4.0 }, Coordinate { x: 0.0, y: 4.0 }, Coordinate { x: 0.0, y: 0.0 },
+        ]);
+        let result = offset_polygon_with_fill_rule(&twice, 0.5, 10.0, FillRule::AbsGeqTwo).unwrap();
+        assert!(result.len() == 1 && result[0].1 == 2, "A ring winding around an area twice has a winding number of 2 there");
+        assert!(result[0].0.0.iter().all(|p| p.x > -0.5 - 1e-9 && p.x < 4.5 + 1e-9 && p.y > -0.5 - 1e-9 && p.y < 4.5 + 1e-9), "Incorrect offset of a ring winding twice");
+        assert!(offset_polygon_with_fill_rule(&twice, 0.5, 10.0, FillRule::EvenOdd).unwrap().is_empty(), "Even winding numbers are not filled with the even-odd rule");
+    }
+    #[test]
+    fn empty_rings() {
+        let square = LineString(vec![
+            Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 4.0, y: 0.0 }, Coordinate { x: 4.0, y: 4.0 }, Coordinate { x: 0.0, y: 4.0 }, Coordinate { x: 0.0, y: 0.0 },
+        ]);
+        let result = offset_polygons(&MultiPolygon(vec![Polygon::new(square, vec![LineString(vec![])])]), 1.0, 10.0).unwrap();
+        assert!(result.0.len() == 1 && result.0[0].interiors().is_empty(), "An empty hole has to be ignored");
+        let result = offset_polygons(&MultiPolygon(vec![Polygon::new(LineString(vec![]), vec![])]), 1.0, 10.0).unwrap();
+        assert!(result.0.is_empty(), "An empty exterior ring results in no polygon");
+    }
 }
@@ -0,0 +1,341 @@
+//! The `offset-polygon` command-line tool. It reads geometries from WKT, GeoJSON or SVG, offsets every one of them and writes the
+//! results. Only built with the `cli` feature.
+
+use std::{env, fs, process};
+use std::io::{self, Read, Write};
+
+use geo_types::{LineString, Polygon, MultiPolygon};
+use serde_json::Map;
+use offset_polygon::geojson::{Feature, GeoJsonError, read_feature_results, write_features, offset_feature};
+use offset_polygon::wkt::{Geometry, read_wkt, write_wkt};
+use offset_polygon::svg::{parse_path, document};
+use offset_polygon::point_in_polygon::nest;
+
+const USAGE: &str = "Usage: offset-polygon [OPTIONS] [INPUT]
+
+Offsets every geometry of INPUT (or standard input) and writes the results to standard output.
+
+Options:
+  -d, --offset DISTANCE   Positive distances expand the geometries, negative ones shrink them.
+      --property NAME     Takes the distance of each GeoJSON feature from this property. Features without it
+                          are offset by --offset.
+      --join STYLE        round (default) or bevel.
+      --arc-detail N      Number of vertices of a full circle in round joins, at least 3 (default 16).
+      --from FORMAT       Input format: wkt, geojson or svg. Detected from the file name or the content by default.
+      --to FORMAT         Output format: wkt, geojson or svg. Taken from the output file name or the input by default.
+      --tolerance T       Maximum distance of the flattened curves of SVG paths from the curves (default 0.01).
+  -o, --output FILE       Writes the results to FILE instead of standard output.
+  -h, --help              Prints this help.
+
+WKT input has one geometry per line, every SVG path element is one geometry.
+
+Exit codes:
+  0  All geometries were offset.
+  1  Some geometries couldn't be read or offset, they are missing in the output.
+  2  Invalid arguments.
+  3  The input can't be read or parsed, or the output can't be written.
+";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Wkt,
+    GeoJson,
+    Svg,
+}
+
+impl Format {
+    fn from_name(name: &str) -> Option<Format> {
+        match name.to_ascii_lowercase().as_str() {
+            "wkt" => Some(Format::Wkt),
+            "geojson" | "json" => Some(Format::GeoJson),
+            "svg" => Some(Format::Svg),
+            _ => None,
+        }
+    }
+
+    fn from_path(path: &str) -> Option<Format> {
+        path.rsplit_once('.').and_then(|(_, extension)| Format::from_name(extension))
+    }
+
+    /// Guesses the format of input without a file name from its first character.
+    fn detect(text: &str) -> Format {
+        match text.trim_start().chars().next() {
+            Some('{') => Format::GeoJson,
+            Some('<') => Format::Svg,
+            _ => Format::Wkt,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Options {
+    offset: Option<f64>,
+    property: Option<String>,
+    arcdetail: f64,
+    from: Option<Format>,
+    to: Option<Format>,
+    tolerance: f64,
+    input: Option<String>,
+    output: Option<String>,
+}
+
+/// Parses the command-line arguments (without the program name). Returns `None` if the help was requested.
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        offset: None,
+        property: None,
+        arcdetail: 16.0,
+        from: None,
+        to: None,
+        tolerance: 0.01,
+        input: None,
+        output: None,
+    };
+    let mut bevel = false;
+    while let Some(arg) = args.next() {
+        // both `--name value` and `--name=value` are accepted
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name.to_string(), Some(value.to_string())),
+            _ => (arg.clone(), None),
+        };
+        let mut value = || inline.clone().or_else(|| args.next()).ok_or(format!("Missing value for {}", name));
+        let number = |value: String| value.parse::<f64>().ok().filter(|number| number.is_finite()).ok_or(format!("Invalid number {} for {}", value, name));
+        match name.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-d" | "--offset" => options.offset = Some(number(value()?)?),
+            "--property" => options.property = Some(value()?),
+            "--join" => bevel = match value()?.as_str() {
+                "round" => false,
+                "bevel" => true,
+                style => return Err(format!("Unknown join style {}", style)),
+            },
+            "--arc-detail" => {
+                options.arcdetail = number(value()?)?;
+                if options.arcdetail < 3.0 {
+                    return Err("The arc detail has to be at least 3".to_string());
+                }
+            },
+            "--from" | "--to" => {
+                let format = value()?;
+                let format = Format::from_name(&format).ok_or(format!("Unknown format {}", format))?;
+                if name == "--from" { options.from = Some(format) } else { options.to = Some(format) }
+            },
+            "--tolerance" => options.tolerance = number(value()?)?,
+            "-o" | "--output" => options.output = Some(value()?),
+            _ if name.starts_with('-') && name != "-" => return Err(format!("Unknown option {}", name)),
+            _ if options.input.is_some() => return Err(format!("Unexpected argument {}", name)),
+            _ => options.input = Some(arg).filter(|input| input != "-"),
+        }
+    }
+    if options.offset.is_none() && options.property.is_none() {
+        return Err("Missing offset".to_string());
+    }
+    if options.tolerance <= 0.0 {
+        return Err("The tolerance has to be positive".to_string());
+    }
+    if bevel {
+        // with a step of half a circle no vertices are added between the ends of the offset edges
+        options.arcdetail = 2.0;
+    }
+    Ok(Some(options))
+}
+
+fn feature(geometry: MultiPolygon<f64>) -> Feature<f64> {
+    Feature {
+        id: None,
+        geometry: Some(geometry),
+        properties: Map::new(),
+    }
+}
+
+/// Returns the values of the `d` attributes of all `path` elements.
+fn path_elements(text: &str) -> Vec<&str> {
+    text.match_indices("<path").filter_map(|(start, _)| {
+        let element = &text[start..start + text[start..].find('>')?];
+        let attribute = element.char_indices().find(|&(idx, c)| c.is_whitespace() && element[idx + 1..].starts_with("d="))?.0 + 3;
+        let quote = element[attribute..].chars().next().filter(|&c| c == '"' || c == '\'')?;
+        let value = &element[attribute + 1..];
+        Some(&value[..value.find(quote)?])
+    }).collect()
+}
+
+/// Reads all geometries of the input as features, WKT and SVG geometries get no id and no properties. Every geometry is read on
+/// its own, so that an invalid one only results in an error for that geometry. Fails if the input can't be split into geometries.
+fn read_input(text: &str, format: Format, tolerance: f64) -> Result<Vec<Result<Feature<f64>, String>>, String> {
+    match format {
+        Format::Wkt => Ok(text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()).map(|(idx, line)| {
+            match read_wkt(line).map_err(|error| format!("Line {}: {}", idx + 1, error))? {
+                Geometry::LineString(ring) => Ok(feature(MultiPolygon(vec![Polygon::new(ring, Vec::new())]))),
+                Geometry::Polygon(polygon) => Ok(feature(MultiPolygon(vec![polygon]))),
+                Geometry::MultiPolygon(polygons) => Ok(feature(polygons)),
+            }
+        }).collect()),
+        Format::GeoJson => Ok(read_feature_results(text).map_err(|error| error.to_string())?.into_iter().map(|feature| feature.map_err(message)).collect()),
+        Format::Svg => Ok(path_elements(text).into_iter().map(|d| {
+            parse_path(d, tolerance).map(|rings| feature(nest(rings))).map_err(|error| error.to_string())
+        }).collect()),
+    }
+}
+
+/// Returns the message of an error of a single feature without the feature index, which is reported separately.
+fn message(error: GeoJsonError) -> String {
+    match error {
+        GeoJsonError::Feature { message, .. } => format!("{}.", message),
+        error => error.to_string(),
+    }
+}
+
+/// Offsets all features with [`offset_feature`]. Geometries that can't be read or offset are left out of the result, with a
+/// message for each of them.
+fn offset_features(features: &[Result<Feature<f64>, String>], options: &Options) -> (Vec<Feature<f64>>, Vec<String>) {
+    let mut results = Vec::new();
+    let mut messages = Vec::new();
+    for (index, feature) in features.iter().enumerate() {
+        match feature.clone().and_then(|feature| offset_feature(&feature, index, options.property.as_deref(), options.offset, options.arcdetail).map_err(message)) {
+            Ok(feature) => results.push(feature),
+            Err(message) => messages.push(format!("Geometry {}: {}", index, message)),
+        }
+    }
+    (results, messages)
+}
+
+fn write_output(features: &[Feature<f64>], format: Format) -> String {
+    match format {
+        Format::Wkt => features.iter().map(|feature| {
+            let geometry = match &feature.geometry {
+                Some(MultiPolygon(polygons)) if polygons.len() == 1 => Geometry::Polygon(polygons[0].clone()),
+                Some(geometry) => Geometry::MultiPolygon(geometry.clone()),
+                None => Geometry::MultiPolygon(MultiPolygon(Vec::new())),
+            };
+            write_wkt(&geometry) + "\n"
+        }).collect(),
+        Format::GeoJson => write_features(features) + "\n",
+        Format::Svg => {
+            let rings: Vec<LineString<f64>> = features.iter().flat_map(|feature| feature.geometry.iter().flat_map(|geometry| geometry.0.iter()))
+                .flat_map(|polygon| std::iter::once(polygon.exterior()).chain(polygon.interiors())).cloned().collect();
+            document(&rings)
+        },
+    }
+}
+
+fn run<I: Iterator<Item = String>>(args: I) -> i32 {
+    let options = match parse_args(args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{}", USAGE);
+            return 0;
+        },
+        Err(message) => {
+            eprintln!("{}.\n\n{}", message, USAGE);
+            return 2;
+        },
+    };
+    let text = match &options.input {
+        Some(path) => fs::read_to_string(path).map_err(|error| format!("Can't read {}: {}", path, error)),
+        None => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text).map(|_| text).map_err(|error| format!("Can't read standard input: {}", error))
+        },
+    };
+    let text = match text {
+        Ok(text) => text,
+        Err(message) => {
+            eprintln!("{}", message);
+            return 3;
+        },
+    };
+    let from = options.from.or_else(|| options.input.as_deref().and_then(Format::from_path)).unwrap_or_else(|| Format::detect(&text));
+    let features = match read_input(&text, from, options.tolerance) {
+        Ok(features) => features,
+        Err(message) => {
+            eprintln!("{}", message);
+            return 3;
+        },
+    };
+
+    let (results, messages) = offset_features(&features, &options);
+    for message in &messages {
+        eprintln!("{}", message);
+    }
+
+    let to = options.to.or_else(|| options.output.as_deref().and_then(Format::from_path)).unwrap_or(from);
+    let output = write_output(&results, to);
+    let written = match &options.output {
+        Some(path) => fs::write(path, output).map_err(|error| format!("Can't write {}: {}", path, error)),
+        None => io::stdout().write_all(output.as_bytes()).map_err(|error| format!("Can't write standard output: {}", error)),
+    };
+    if let Err(message) = written {
+        eprintln!("{}", message);
+        return 3;
+    }
+    if messages.is_empty() { 0 } else { 1 }
+}
+
+fn main() {
+    process::exit(run(env::args().skip(1)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use offset_polygon::geojson::DISTANCE_PROPERTY;
+    fn args(line: &str) -> Result<Option<Options>, String> {
+        parse_args(line.split_whitespace().map(String::from))
+    }
+    #[test]
+    fn arguments() {
+        let options = args("-d -2.5 --join bevel --to=geojson shapes.wkt").unwrap().unwrap();
+        assert!(options.offset == Some(-2.5) && options.arcdetail == 2.0 && options.to == Some(Format::GeoJson) && options.input.as_deref() == Some("shapes.wkt"), "Incorrect options");
+        assert!(args("--property buffer -").unwrap().unwrap().input.is_none(), "- has to read standard input");
+        assert!(args("--help").unwrap().is_none(), "Help has to be requested");
+        assert!(args("shapes.wkt") == Err("Missing offset".to_string()) && args("-d x") == Err("Invalid number x for -d".to_string()), "Invalid arguments have to be reported");
+        assert!(args("-d 1 --arc-detail 2") == Err("The arc detail has to be at least 3".to_string()), "Too small arc detail has to be reported");
+        assert!(Format::from_path("out.json") == Some(Format::GeoJson) && Format::detect("  <svg>") == Format::Svg, "Incorrect format detection");
+    }
+    #[test]
+    fn convert() {
+        let options = args("-d 1 --property buffer").unwrap().unwrap();
+        let features = read_input("POLYGON ((0 0, 10 0, 10 10, 0 10, 0 0))\n\nLINESTRING (20 0, 20 4, 24 4, 24 0)\n", Format::Wkt, 0.01).unwrap();
+        let (results, messages) = offset_features(&features, &options);
+        assert!(results.len() == 2 && messages.is_empty(), "Both geometries have to be offset");
+        // the clockwise line string is expanded as well
+        assert!(results[1].geometry.as_ref().unwrap().0[0].exterior().0.iter().all(|p| p.x >= 19.0 - 1e-9 && p.x <= 25.0 + 1e-9 && p.y >= -1.0 - 1e-9), "Incorrect offset");
+        assert!(write_output(&results, Format::Wkt).lines().all(|line| line.starts_with("POLYGON ((")), "Every result has to be a line");
+
+        let features = read_input("POLYGON ((0 0, 10 0, 10 10, 0 10, 0 0))\nPOLYGON ((0 0, 1 x))\n", Format::Wkt, 0.01).unwrap();
+        let (results, messages) = offset_features(&features, &options);
+        assert!(results.len() == 1 && messages.len() == 1 && messages[0].starts_with("Geometry 1: Line 2: "), "Parse errors have to be reported for their geometry only");
+
+        let features = read_input("POLYGON EMPTY\nLINESTRING EMPTY\nPOLYGON ((0 0, 1 0, 1 1, 0 0), EMPTY)\n", Format::Wkt, 0.01).unwrap();
+        let (results, messages) = offset_features(&features, &options);
+        assert!(results.len() == 3 && messages.is_empty(), "Empty geometries have to be passed through");
+        assert!(results[0].geometry.as_ref().unwrap().0.is_empty() && results[1].geometry.as_ref().unwrap().0.is_empty(), "Empty geometries have to stay empty");
+        assert!(results[2].geometry.as_ref().unwrap().0.len() == 1 && results[2].geometry.as_ref().unwrap().0[0].interiors().is_empty(), "Empty holes have to be ignored");
+
+        let geojson = r#"{ "type": "FeatureCollection", "features": [
+            { "type": "Feature", "properties": { "buffer": "wide" }, "geometry": { "type": "Polygon", "coordinates": [[[0, 0], [4, 0], [4, 4], [0, 4], [0, 0]]] } },
+            { "type": "Feature", "properties": { "buffer": -1 }, "geometry": { "type": "Polygon", "coordinates": [[[0, 0], [4, 0], [4, 4], [0, 4], [0, 0]]] } }
+        ] }"#;
+        let (results, messages) = offset_features(&read_input(geojson, Format::GeoJson, 0.01).unwrap(), &options);
+        assert!(results.len() == 1 && results[0].properties[DISTANCE_PROPERTY] == json!(-1.0), "Valid feature has to be offset");
+        assert!(messages == vec!["Geometry 0: Property buffer is not a number.".to_string()], "Invalid feature has to be reported");
+
+        let geojson = r#"{ "type": "FeatureCollection", "features": [
+            { "type": "Feature", "properties": {}, "geometry": { "type": "Point", "coordinates": [0, 0] } },
+            { "type": "Feature", "properties": {}, "geometry": { "type": "Polygon", "coordinates": [[[0, 0], [4, 0], [4, 4], [0, 4], [0, 0]]] } }
+        ] }"#;
+        let (results, messages) = offset_features(&read_input(geojson, Format::GeoJson, 0.01).unwrap(), &options);
+        assert!(results.len() == 1 && messages == vec!["Geometry 0: Unsupported geometry type Point.".to_string()], "Unsupported geometries have to be reported for their feature only");
+    }
+    #[test]
+    fn svg_paths() {
+        let text = "<svg><path id=\"frame\" d=\"M0 0 H10 V10 H0 Z M2 2 H8 V8 H2 Z M4 4 H6 V6 H4 Z\"/>\n<path fill='red' d='M20 0 h2 v2 h-2 z'/></svg>";
+        let features = read_input(text, Format::Svg, 0.01).unwrap();
+        let frame = features[0].as_ref().unwrap().geometry.as_ref().unwrap();
+        assert!(features.len() == 2 && frame.0.len() == 2 && frame.0.iter().map(|polygon| polygon.interiors().len()).sum::<usize>() == 1, "Rings have to be nested by the even-odd rule");
+        let (results, _) = offset_features(&features, &args("-d 0.5").unwrap().unwrap());
+        assert!(results[0].geometry.as_ref().unwrap().0.len() == 2 && results[0].geometry.as_ref().unwrap().0[0].interiors().len() == 1, "Frame has to keep its hole");
+        assert!(write_output(&results, Format::Svg).matches("<path").count() == 1, "All rings have to be written into one path");
+    }
+}
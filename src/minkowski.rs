@@ -0,0 +1,454 @@
+use geo_types::{LineString, Coordinate};
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ops::{AddAssign, SubAssign};
+
+use super::{segment_normal, is_left, oriented_ring, extract_regions, CombinatorialExplosionError, OffsetError, MAX_INTERSECTIONS};
+
+/// A convex polygon in counter-clockwise order, together with the angles of its edge normals.
+struct ConvexShape<N: Num + Copy + NumCast + PartialOrd> {
+    vertices: Vec<Coordinate<N>>,
+    angles: Vec<N>,
+}
+
+impl<N> ConvexShape<N> where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    /// Takes the shape in counter-clockwise order.
+    fn new(shape: &LineString<N>) -> ConvexShape<N> {
+        let mut vertices: Vec<Coordinate<N>> = shape.0.clone();
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        // drop duplicate vertices, they would result in edges without a normal
+        let mut idx = 0;
+        while vertices.len() > 1 && idx < vertices.len() {
+            if segment_normal(vertices[idx], vertices[(idx+1) % vertices.len()]).is_none() {
+                vertices.remove(idx);
+            } else {
+                idx += 1;
+            }
+        }
+        let angles = (0..vertices.len()).map(|idx| {
+            segment_normal(vertices[idx], vertices[(idx+1) % vertices.len()]).map(|(normal, _)| normal.y.atan2(normal.x)).unwrap_or_else(N::zero)
+        }).collect();
+        ConvexShape {
+            vertices,
+            angles,
+        }
+    }
+
+    /// Returns whether the shape is convex: it turns left (or goes straight on) at every vertex and winds around only once.
+    fn is_convex(&self) -> bool {
+        let count = self.vertices.len();
+        let mut turn = N::zero();
+        for idx in 0..count {
+            let (p0, p1, p2) = (self.vertices[idx], self.vertices[(idx+1) % count], self.vertices[(idx+2) % count]);
+            let (d0, d1) = (Coordinate { x: p1.x - p0.x, y: p1.y - p0.y }, Coordinate { x: p2.x - p1.x, y: p2.y - p1.y });
+            let cross = d0.x * d1.y - d0.y * d1.x;
+            if cross < N::zero() {
+                return false;
+            }
+            turn = turn + cross.atan2(d0.x * d1.x + d0.y * d1.y);
+        }
+        turn < N::from_f32(3.0).unwrap() * N::PI()
+    }
+
+    /// Returns the index of the vertex that is farthest in the direction of the given angle. If an edge of the shape is facing
+    /// exactly in that direction, its first vertex is returned.
+    fn support(&self, angle: N) -> usize {
+        let full = N::from_f32(2.0).unwrap() * N::PI();
+        let normalize = |angle: N| {
+            let angle = angle % full;
+            if angle.is_sign_negative() { angle + full } else { angle }
+        };
+        (0..self.vertices.len()).find(|&idx| {
+            let previous = self.angles[(idx + self.vertices.len() - 1) % self.vertices.len()];
+            let offset = normalize(angle - previous);
+            offset > N::zero() && offset <= normalize(self.angles[idx] - previous)
+        }).unwrap_or(0)
+    }
+}
+
+/// Calculates the Minkowski sum of the given polygon and a convex shape, which expands the polygon by that shape. This works like
+/// [`offset_polygon`](fn.offset_polygon.html), except that every edge is moved by the vertex of the shape that is farthest in the
+/// direction of its normal and that the vertices of the shape are used in the corners instead of arcs.
+///
+/// # Arguments
+///
+/// * `polygon` - A polygon. It has to be closed (the last coordinate has to be the same as the first coordinate)
+/// * `shape` - A convex polygon, relative to the origin. The orientation of both polygons doesn't matter.
+///
+/// Returns the same as [`offset_polygon`](fn.offset_polygon.html). Fails with `OffsetError::InvalidArgument` if the shape isn't
+/// convex, use [`minkowski_sum`](fn.minkowski_sum.html) for those.
+pub fn minkowski_sum_convex<N>(polygon: &LineString<N>, shape: &LineString<N>) -> Result<Vec<LineString<N>>, OffsetError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    if polygon.0.is_empty() || shape.0.is_empty() {
+        return Ok(vec![LineString(Vec::new())]);
+    }
+    let (polygon, shape) = (oriented_ring(polygon, false), ConvexShape::new(&oriented_ring(shape, false)));
+    if !shape.is_convex() {
+        return Err(OffsetError::InvalidArgument("the shape has to be convex".to_string()));
+    }
+    let count = shape.vertices.len();
+
+    // each segment is the original edge, its normal and the index of the vertex of the shape it is moved by
+    let lines: Vec<_> = (0..(polygon.0.len()-1)).filter_map(|idx| {
+        let (p0, p1) = (polygon.0[idx], polygon.0[idx+1]);
+        segment_normal(p0, p1).map(|(normal, _)| (p0, p1, normal, shape.support(normal.y.atan2(normal.x))))
+    }).collect();
+    let translate = |p: Coordinate<N>, vertex: usize| Coordinate {
+        x: p.x + shape.vertices[vertex].x,
+        y: p.y + shape.vertices[vertex].y,
+    };
+
+    let mut connected = Vec::new();
+    for idx in 0..lines.len() {
+        let ((p0, p1, normal0, vertex0), (_, _, normal1, vertex1)) = (lines[idx], lines[(idx+1) % lines.len()]);
+        connected.extend_from_slice(&[translate(p0, vertex0), translate(p1, vertex0)]);
+        if vertex0 == vertex1 { // the next segment starts at the same point
+            connected.pop();
+        } else if normal0.x * normal1.y - normal0.y * normal1.x >= N::zero() { // convex corner, walk forward along the shape
+            let mut vertex = (vertex0 + 1) % count;
+            while vertex != vertex1 {
+                connected.push(translate(p1, vertex));
+                vertex = (vertex + 1) % count;
+            }
+        } else { // concave corner, walk backwards along the shape
+            let mut vertex = (vertex0 + count - 1) % count;
+            while vertex != vertex1 {
+                connected.push(translate(p1, vertex));
+                vertex = (vertex + count - 1) % count;
+            }
+        }
+    }
+    connected.push(connected[0]);
+
+    Ok(extract_regions(connected)?)
+}
+
+/// Returns the vertices of the ring in counter-clockwise order, without the closing coordinate and without duplicate vertices.
+fn counter_clockwise<N>(ring: &LineString<N>) -> Vec<Coordinate<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let mut vertices: Vec<Coordinate<N>> = Vec::with_capacity(ring.0.len());
+    for p in ring.0.iter() {
+        if vertices.last().map(|last| segment_normal(*last, *p).is_some()).unwrap_or(true) {
+            vertices.push(*p);
+        }
+    }
+    while vertices.len() > 1 && segment_normal(vertices[vertices.len()-1], vertices[0]).is_none() {
+        vertices.pop();
+    }
+    let area = (0..vertices.len()).fold(N::zero(), |area, idx| {
+        let (p0, p1) = (vertices[idx], vertices[(idx+1) % vertices.len()]);
+        area + p0.x * p1.y - p1.x * p0.y
+    });
+    if area.is_sign_negative() {
+        vertices.reverse();
+    }
+    vertices
+}
+
+/// Returns the angles of the edge directions of the ring (edge `i` goes from vertex `i` to vertex `i+1`).
+fn directions<N>(vertices: &[Coordinate<N>]) -> Vec<N>
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    (0..vertices.len()).map(|idx| {
+        let (p0, p1) = (vertices[idx], vertices[(idx+1) % vertices.len()]);
+        (p1.y - p0.y).atan2(p1.x - p0.x)
+    }).collect()
+}
+
+/// Calculates the Minkowski sum of two arbitrary simple polygons. The convolution of the two polygons is built following the paper
+/// by Chen & McMains this crate is based on, and the sum is the area with a non-zero winding number in respect to it. Since the
+/// edges of the convolution overlap wherever the polygons have parallel edges, they are split where they cross or touch each
+/// other instead of being resolved like the raw offset curve of [`offset_polygon`](fn.offset_polygon.html).
+///
+/// # Arguments
+///
+/// * `polygon` - A simple polygon. It has to be closed (the last coordinate has to be the same as the first coordinate)
+/// * `shape` - Another simple polygon, relative to the origin. The orientation of both polygons doesn't matter.
+///
+/// Returns a vector of polygons. Holes in the sum are returned as separate polygons in clockwise order. The error is the same as
+/// the one of `offset_polygon`.
+pub fn minkowski_sum<N>(polygon: &LineString<N>, shape: &LineString<N>) -> Result<Vec<LineString<N>>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let (a, b) = (counter_clockwise(polygon), counter_clockwise(shape));
+    if a.len() < 2 || b.len() < 2 {
+        return Ok(vec![LineString(Vec::new())]);
+    }
+    let (directions_a, directions_b) = (directions(&a), directions(&b));
+    let full = N::from_f32(2.0).unwrap() * N::PI();
+    let normalize = |angle: N| {
+        let angle = angle % full;
+        if angle.is_sign_negative() { angle + full } else { angle }
+    };
+
+    // The convolution consists of the edges of one polygon placed at those vertices of the other polygon where the edge's
+    // direction lies counter-clockwise between the directions of the edges meeting there. Ties are resolved in favor of the
+    // first polygon, so that every vertex of the convolution has as many outgoing as incoming edges.
+    let vertex = |i: usize, j: usize| Coordinate { x: a[i].x + b[j].x, y: a[i].y + b[j].y };
+    let mut edges = Vec::new();
+    for i in 0..a.len() {
+        for j in 0..b.len() {
+            let previous = directions_b[(j + b.len() - 1) % b.len()];
+            let (angle, range) = (normalize(directions_a[i] - previous), normalize(directions_b[j] - previous));
+            if angle > N::zero() && angle <= range {
+                edges.push((vertex(i, j), vertex((i+1) % a.len(), j)));
+            }
+            let previous = directions_a[(i + a.len() - 1) % a.len()];
+            let (angle, range) = (normalize(directions_b[j] - previous), normalize(directions_a[i] - previous));
+            if angle >= N::zero() && angle < range {
+                edges.push((vertex(i, j), vertex(i, (j+1) % b.len())));
+            }
+        }
+    }
+
+    // Edges of the convolution often overlap or meet in the middle of another edge if the polygons have parallel edges, which
+    // can't be resolved by walking along the curves like the raw offset curve. Instead, the edges are split until they only meet
+    // in their end points and every edge that separates a non-zero winding number from a zero one is kept.
+    let (points, counts) = split_edges(&edges)?;
+    let kept: Vec<(usize, usize)> = counts.iter().filter_map(|&((p0, p1), count)| {
+        let right = winding_number_right(points[p0], points[p1], &points, &counts);
+        match (right + count != 0, right != 0) {
+            (true, false) => Some((p0, p1)),
+            (false, true) => Some((p1, p0)),
+            _ => None,
+        }
+    }).collect();
+
+    // Link the kept edges into rings. At every point, the ring continues with the first outgoing edge clockwise from the edge it
+    // came from, so that the filled area stays on its left and rings only touching in a point are kept apart.
+    let mut outgoing: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (idx, edge) in kept.iter().enumerate() {
+        outgoing.entry(edge.0).or_default().push(idx);
+    }
+    let angle = |p0: usize, p1: usize| (points[p1].y - points[p0].y).atan2(points[p1].x - points[p0].x);
+    let next = |edge: usize| {
+        let (from, at) = kept[edge];
+        let back = angle(at, from);
+        outgoing.get(&at).and_then(|candidates| candidates.iter().cloned().map(|candidate| {
+            let turn = normalize(back - angle(at, kept[candidate].1));
+            (if turn > N::zero() { turn } else { full }, candidate)
+        }).fold(None, |best: Option<(N, usize)>, (turn, candidate)| match best {
+            Some((best_turn, _)) if best_turn <= turn => best,
+            _ => Some((turn, candidate)),
+        })).map(|(_, candidate)| candidate)
+    };
+    let mut used = vec![false; kept.len()];
+    let mut rings = Vec::new();
+    for start in 0..kept.len() {
+        let mut ring = vec![kept[start].0];
+        let mut edge = Some(start);
+        while let Some(current) = edge.filter(|current| !used[*current]) {
+            used[current] = true;
+            ring.push(kept[current].1);
+            edge = next(current);
+        }
+        if ring.len() > 3 && ring.first() == ring.last() {
+            let ring = without_collinear_points(&ring, &points);
+            if ring.len() > 3 {
+                rings.push(LineString(ring));
+            }
+        }
+    }
+    Ok(rings)
+}
+
+/// Returns the tolerance within which points of the edges are considered to be the same.
+fn tolerance<N>(edges: &[(Coordinate<N>, Coordinate<N>)]) -> N
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let scale = edges.iter().flat_map(|(p0, p1)| vec![p0.x, p0.y, p1.x, p1.y]).fold(N::one(), |scale, value| scale.max(value.abs()));
+    N::epsilon().sqrt() * scale
+}
+
+/// Returns where the point is on the segment (between 0 and 1), if it lies on the segment but not on one of its end points.
+fn position_on_segment<N>(p0: Coordinate<N>, p1: Coordinate<N>, point: Coordinate<N>, tolerance: N) -> Option<N>
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let length = (p1.x - p0.x).hypot(p1.y - p0.y);
+    let along = ((point.x - p0.x) * (p1.x - p0.x) + (point.y - p0.y) * (p1.y - p0.y)) / length;
+    if length <= tolerance || is_left(p0, p1, point).abs() > tolerance * length || along <= tolerance || along >= length - tolerance {
+        return None;
+    }
+    Some(along / length)
+}
+
+/// Edges as pairs of point indices, each with how often it is passed from its first to its second point, minus how often it is
+/// passed the other way.
+type EdgeCounts = Vec<((usize, usize), isize)>;
+
+/// Splits the directed edges at their crossings and at the end points of other edges lying on them, so that they only meet in
+/// their end points and overlapping edges become the same. Points closer than the tolerance are merged. Returns the points and
+/// the counts of the split edges. Edges passed equally often in both directions are left out.
+fn split_edges<N>(edges: &[(Coordinate<N>, Coordinate<N>)]) -> Result<(Vec<Coordinate<N>>, EdgeCounts), CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let tolerance = tolerance(edges);
+    let mut splits: Vec<Vec<(N, Coordinate<N>)>> = vec![Vec::new(); edges.len()];
+    let mut crossings = 0;
+    for i in 0..edges.len() {
+        for j in (i+1)..edges.len() {
+            let ((p0, p1), (q0, q1)) = (edges[i], edges[j]);
+            for q in [q0, q1].iter() {
+                if let Some(t) = position_on_segment(p0, p1, *q, tolerance) {
+                    splits[i].push((t, *q));
+                }
+            }
+            for p in [p0, p1].iter() {
+                if let Some(t) = position_on_segment(q0, q1, *p, tolerance) {
+                    splits[j].push((t, *p));
+                }
+            }
+            let denominator = (p1.x - p0.x) * (q1.y - q0.y) - (p1.y - p0.y) * (q1.x - q0.x);
+            if denominator == N::zero() {
+                continue;
+            }
+            let t = ((q0.x - p0.x) * (q1.y - q0.y) - (q0.y - p0.y) * (q1.x - q0.x)) / denominator;
+            let point = Coordinate { x: p0.x + (p1.x - p0.x) * t, y: p0.y + (p1.y - p0.y) * t };
+            if let (Some(t), Some(u)) = (position_on_segment(p0, p1, point, tolerance), position_on_segment(q0, q1, point, tolerance)) {
+                crossings += 1;
+                if crossings > MAX_INTERSECTIONS {
+                    return Err(CombinatorialExplosionError);
+                }
+                splits[i].push((t, point));
+                splits[j].push((u, point));
+            }
+        }
+    }
+
+    let mut points: Vec<Coordinate<N>> = Vec::new();
+    let mut index = |point: Coordinate<N>| {
+        points.iter().position(|p| (p.x - point.x).abs() <= tolerance && (p.y - point.y).abs() <= tolerance).unwrap_or_else(|| {
+            points.push(point);
+            points.len() - 1
+        })
+    };
+    let mut counts: EdgeCounts = Vec::new();
+    for (&(p0, p1), splits) in edges.iter().zip(splits.iter_mut()) {
+        splits.sort_by(|(t0, _), (t1, _)| t0.partial_cmp(t1).unwrap_or(Ordering::Equal));
+        let indices: Vec<usize> = std::iter::once(p0).chain(splits.iter().map(|(_, point)| *point)).chain(std::iter::once(p1)).map(&mut index).collect();
+        for pair in indices.windows(2).filter(|pair| pair[0] != pair[1]) {
+            let (key, count) = if pair[0] < pair[1] { ((pair[0], pair[1]), 1) } else { ((pair[1], pair[0]), -1) };
+            match counts.iter_mut().find(|(other, _)| *other == key) {
+                Some((_, total)) => *total += count,
+                None => counts.push((key, count)),
+            }
+        }
+    }
+    counts.retain(|(_, count)| *count != 0);
+    Ok((points, counts))
+}
+
+/// Returns the winding number of the split edges right next to the middle of the edge from `p0` to `p1`, on its right side. It
+/// is found by counting the edges crossing a ray from the middle to the right.
+fn winding_number_right<N>(p0: Coordinate<N>, p1: Coordinate<N>, points: &[Coordinate<N>], counts: &[((usize, usize), isize)]) -> isize
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    let half = N::from_f32(0.5).unwrap();
+    let middle = Coordinate { x: (p0.x + p1.x) * half, y: (p0.y + p1.y) * half };
+    let direction = Coordinate { x: p1.x - p0.x, y: p1.y - p0.y };
+    // coordinates with the ray as x axis and the edge as y axis
+    let local = |p: Coordinate<N>| {
+        let (dx, dy) = (p.x - middle.x, p.y - middle.y);
+        (dx * direction.y - dy * direction.x, dx * direction.x + dy * direction.y)
+    };
+    counts.iter().filter(|((q0, q1), _)| (points[*q0], points[*q1]) != (p0, p1) && (points[*q0], points[*q1]) != (p1, p0)).map(|&((q0, q1), count)| {
+        let ((x0, y0), (x1, y1)) = (local(points[q0]), local(points[q1]));
+        if (y0 <= N::zero()) == (y1 <= N::zero()) || x0 + (x1 - x0) * (-y0 / (y1 - y0)) <= N::zero() {
+            0
+        } else if y1 > y0 {
+            count
+        } else {
+            -count
+        }
+    }).sum()
+}
+
+/// Returns the closed ring of the points without the points where it goes straight on.
+fn without_collinear_points<N>(ring: &[usize], points: &[Coordinate<N>]) -> Vec<Coordinate<N>>
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let count = ring.len() - 1;
+    let mut result: Vec<Coordinate<N>> = (0..count).filter(|idx| {
+        let (p0, p1, p2) = (points[ring[(idx + count - 1) % count]], points[ring[*idx]], points[ring[(idx+1) % count]]);
+        is_left(p0, p1, p2) != N::zero() || (p1.x - p0.x) * (p2.x - p1.x) + (p1.y - p0.y) * (p2.y - p1.y) < N::zero()
+    }).map(|idx| points[ring[idx]]).collect();
+    if let Some(first) = result.first().cloned() {
+        result.push(first);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn area(line: &LineString<f64>) -> f64 {
+        line.0.windows(2).map(|p| p[0].x * p[1].y - p[1].x * p[0].y).sum::<f64>() / 2.0
+    }
+    #[test]
+    fn rectangle() {
+        let input = LineString(vec![
+            Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 10.0, y: 0.0 }, Coordinate { x: 10.0, y: 10.0 }, Coordinate { x: 0.0, y: 10.0 }, Coordinate { x: 0.0, y: 0.0 },
+        ]);
+        let shape = LineString(vec![
+            Coordinate { x: 0.0, y: -1.0 }, Coordinate { x: 2.0, y: 0.0 }, Coordinate { x: 0.0, y: 1.0 }, Coordinate { x: -2.0, y: 0.0 }, Coordinate { x: 0.0, y: -1.0 },
+        ]);
+        let result = minkowski_sum_convex(&input, &shape).unwrap();
+        assert!(result.len() == 1, "rectangle input should result in one polygon");
+        assert!(result[0].0.iter().zip(vec![
+            Coordinate { x: 0.0, y: -1.0 }, Coordinate { x: 10.0, y: -1.0 }, Coordinate { x: 12.0, y: 0.0 }, Coordinate { x: 12.0, y: 10.0 }, Coordinate { x: 10.0, y: 11.0 }, Coordinate { x: 0.0, y: 11.0 }, Coordinate { x: -2.0, y: 10.0 }, Coordinate { x: -2.0, y: 0.0 }, Coordinate { x: 0.0, y: -1.0 },
+        ]).all(|(p0, p1)| (p0.x - p1.x).abs() < f64::epsilon() && (p0.y - p1.y).abs() < f64::epsilon()), "Incorrect Minkowski sum for rectangle");
+        let clockwise: LineString<f64> = input.0.iter().rev().cloned().collect();
+        assert!(minkowski_sum_convex(&clockwise, &shape).unwrap() == result, "The orientation of the polygon doesn't matter");
+    }
+    #[test]
+    fn concave() {
+        let input = LineString(vec![
+            Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 10.0, y: 0.0 }, Coordinate { x: 10.0, y: 5.0 }, Coordinate { x: 5.0, y: 5.0 }, Coordinate { x: 5.0, y: 10.0 }, Coordinate { x: 0.0, y: 10.0 }, Coordinate { x: 0.0, y: 0.0 },
+        ]);
+        // clockwise square, with the origin outside of it
+        let shape = LineString(vec![
+            Coordinate { x: 1.0, y: 1.0 }, Coordinate { x: 1.0, y: 3.0 }, Coordinate { x: 3.0, y: 3.0 }, Coordinate { x: 3.0, y: 1.0 }, Coordinate { x: 1.0, y: 1.0 },
+        ]);
+        let result = minkowski_sum_convex(&input, &shape).unwrap();
+        assert!(result.len() == 1, "L-shaped input should result in one polygon");
+        assert!((area(&result[0]) - 119.0).abs() < 1e-9, "Incorrect Minkowski sum for L-shaped polygon");
+        assert!(result[0].0.iter().all(|p| p.x > 1.0 - 1e-9 && p.y > 1.0 - 1e-9 && p.x < 13.0 + 1e-9 && p.y < 13.0 + 1e-9), "Minkowski sum has to be translated by the shape");
+
+        let result = minkowski_sum(&input, &shape).unwrap();
+        assert!(result.len() == 1, "L-shaped input should result in one polygon");
+        assert!((area(&result[0]) - 119.0).abs() < 1e-9, "Minkowski sum with convex shape has to match minkowski_sum_convex");
+    }
+    #[test]
+    fn not_convex() {
+        let u = LineString(vec![
+            Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 5.0, y: 0.0 }, Coordinate { x: 5.0, y: 5.0 }, Coordinate { x: 4.0, y: 5.0 }, Coordinate { x: 4.0, y: 1.0 }, Coordinate { x: 1.0, y: 1.0 }, Coordinate { x: 1.0, y: 5.0 }, Coordinate { x: 0.0, y: 5.0 }, Coordinate { x: 0.0, y: 0.0 },
+        ]);
+        let square = LineString(vec![
+            Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 1.0, y: 0.0 }, Coordinate { x: 1.0, y: 1.0 }, Coordinate { x: 0.0, y: 1.0 }, Coordinate { x: 0.0, y: 0.0 },
+        ]);
+        assert!(matches!(minkowski_sum_convex(&square, &u), Err(OffsetError::InvalidArgument(_))), "Shapes that aren't convex have to be rejected");
+    }
+    #[test]
+    fn non_convex() {
+        let input = LineString(vec![
+            Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 2.0, y: 0.0 }, Coordinate { x: 2.0, y: 1.0 }, Coordinate { x: 1.0, y: 1.0 }, Coordinate { x: 1.0, y: 2.0 }, Coordinate { x: 0.0, y: 2.0 }, Coordinate { x: 0.0, y: 0.0 },
+        ]);
+        // the second operand is rotated, so that no edges of the convolution overlap exactly
+        let (sin, cos) = 0.3f64.sin_cos();
+        let rotated: LineString<f64> = input.0.iter().map(|p| Coordinate { x: p.x * cos - p.y * sin, y: p.x * sin + p.y * cos }).collect();
+        let result = minkowski_sum(&input, &rotated).unwrap();
+        assert!(result.len() == 1, "Sum of two L-shaped polygons should result in one polygon");
+        assert!((area(&result[0]) - 14.76919584047244).abs() < 1e-9, "Incorrect Minkowski sum of two L-shaped polygons");
+
+        // a thin U-shape summed with a small square closes the gap between its arms at the bottom only
+        let u = LineString(vec![
+            Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 5.0, y: 0.0 }, Coordinate { x: 5.0, y: 5.0 }, Coordinate { x: 4.0, y: 5.0 }, Coordinate { x: 4.0, y: 1.0 }, Coordinate { x: 1.0, y: 1.0 }, Coordinate { x: 1.0, y: 5.0 }, Coordinate { x: 0.0, y: 5.0 }, Coordinate { x: 0.0, y: 0.0 },
+        ]);
+        let square = LineString(vec![
+            Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 1.0, y: 0.0 }, Coordinate { x: 1.0, y: 1.0 }, Coordinate { x: 0.0, y: 1.0 }, Coordinate { x: 0.0, y: 0.0 },
+        ]);
+        let result = minkowski_sum(&u, &square).unwrap();
+        assert!(result.len() == 1, "Sum of a U-shape and a square should result in one polygon");
+        assert!((area(&result[0]) - 28.0).abs() < 1e-9, "Incorrect Minkowski sum of a U-shape and a square");
+        // the edges of the convolution overlap and meet in the middle of other edges if both are on the same grid
+        let result = minkowski_sum(&input, &input).unwrap();
+        assert!(result.len() == 1 && (area(&result[0]) - 13.0).abs() < 1e-9, "Incorrect Minkowski sum of two L-shaped polygons on the same grid");
+        assert!(result[0].0.iter().any(|p| p.x == 3.0 && p.y == 3.0), "The square between the arms of the sum is missing");
+    }
+}
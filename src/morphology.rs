@@ -0,0 +1,196 @@
+use geo_types::{LineString, Coordinate, Polygon, MultiPolygon};
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::ops::{AddAssign, SubAssign};
+
+use super::{offset_polygons, offset_segments, oriented_ring, CombinatorialExplosionError, OffsetError};
+use super::point_in_polygon::segment_distance;
+
+/// Replaces runs of vertices that are closer than `tolerance` to each other by a single vertex. Offsetting an arc back by its
+/// radius collapses it into such a run around its center, which is replaced by the corner where the edges before and after the
+/// run meet (or by the first vertex of the run if there's no such corner close by). Only vertices that come from arcs are
+/// replaced: short edges that lie on one of `edges` (the input edges, offset like the ring) are kept unless they are degenerate.
+fn drop_redundant_vertices<N>(ring: &LineString<N>, tolerance: N, edges: &[(Coordinate<N>, Coordinate<N>)]) -> LineString<N>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    let mut vertices = ring.0.clone();
+    if vertices.len() > 1 && vertices.first() == vertices.last() {
+        vertices.pop();
+    }
+    let count = vertices.len();
+    let distance = |p0: Coordinate<N>, p1: Coordinate<N>| (p1.x - p0.x).hypot(p1.y - p0.y);
+    let epsilon = tolerance * N::from_f32(0.001).unwrap();
+    let on_edge = |p0: Coordinate<N>, p1: Coordinate<N>| edges.iter().any(|(e0, e1)| segment_distance(p0, *e0, *e1) < epsilon && segment_distance(p1, *e0, *e1) < epsilon);
+    let short: Vec<bool> = (0..count).map(|idx| {
+        let (p0, p1) = (vertices[idx], vertices[(idx+1) % count]);
+        let length = distance(p0, p1);
+        length < tolerance && (length < epsilon || !on_edge(p0, p1))
+    }).collect();
+    if short.iter().all(|short| *short) {
+        return LineString(Vec::new());
+    }
+    // start right after a long edge, so that no run wraps around
+    let first = (0..count).find(|idx| !short[(idx + count - 1) % count]).unwrap();
+    let mut result = Vec::with_capacity(count + 1);
+    let mut idx = 0;
+    while idx < count {
+        let start = (first + idx) % count;
+        let mut length = 0;
+        while short[(start + length) % count] {
+            length += 1;
+        }
+        let end = (start + length) % count;
+        if length == 0 {
+            result.push(vertices[start]);
+        } else {
+            let (previous, next) = (vertices[(start + count - 1) % count], vertices[(end + 1) % count]);
+            let (d0, d1) = (Coordinate { x: vertices[start].x - previous.x, y: vertices[start].y - previous.y }, Coordinate { x: next.x - vertices[end].x, y: next.y - vertices[end].y });
+            let denominator = d0.x * d1.y - d0.y * d1.x;
+            let corner = if denominator != N::zero() {
+                let s = ((vertices[end].x - previous.x) * d1.y - (vertices[end].y - previous.y) * d1.x) / denominator;
+                Some(Coordinate { x: previous.x + s * d0.x, y: previous.y + s * d0.y })
+            } else {
+                None
+            };
+            result.push(corner.filter(|corner| distance(*corner, vertices[start]) < tolerance + tolerance).unwrap_or(vertices[start]));
+        }
+        idx += length + 1;
+    }
+    if !result.is_empty() {
+        result.push(result[0]);
+    }
+    LineString(result)
+}
+
+/// Returns the edges of all rings of the polygons, offset like `offset_polygons` does.
+fn offset_edges<N>(polygons: &MultiPolygon<N>, offset: N) -> Vec<(Coordinate<N>, Coordinate<N>)>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    polygons.0.iter().flat_map(|polygon| {
+        std::iter::once(oriented_ring(polygon.exterior(), false))
+            .chain(polygon.interiors().iter().map(|interior| oriented_ring(interior, true)))
+            .collect::<Vec<_>>()
+    }).filter(|ring| ring.0.len() > 1).flat_map(|ring| offset_segments(&ring, |_| offset)).map(|segment| (segment.p0, segment.p1)).collect()
+}
+
+/// Offsets twice, first by `first` and then by `-first`, cleaning up the arc vertices of the first step in between and after.
+fn offset_twice<N>(polygons: MultiPolygon<N>, first: N, arcdetail: N) -> Result<MultiPolygon<N>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let arcstep = N::from_f32(2.0).unwrap() * N::PI() / arcdetail;
+    // an upper bound for the distance between the vertices an arc of the first step collapses into
+    let tolerance = N::from_f32(2.0).unwrap() * first.abs() * (arcstep * N::from_f32(0.25).unwrap()).tan();
+    let cleanup = |polygons: MultiPolygon<N>, edges: &[(Coordinate<N>, Coordinate<N>)]| MultiPolygon(polygons.0.into_iter().map(|polygon| {
+        Polygon::new(drop_redundant_vertices(polygon.exterior(), tolerance, edges), polygon.interiors().iter().map(|interior| drop_redundant_vertices(interior, tolerance, edges)).collect())
+    }).filter(|polygon| polygon.exterior().0.len() > 3).collect());
+    let intermediate = cleanup(offset_polygons(&polygons, first, arcstep)?, &offset_edges(&polygons, first));
+    Ok(cleanup(offset_polygons(&intermediate, -first, arcstep)?, &offset_edges(&polygons, N::zero())))
+}
+
+/// Fails if the radius of an opening or closing isn't positive and finite.
+fn check_radius<N: Float>(radius: N) -> Result<(), OffsetError> {
+    if radius.is_finite() && radius > N::zero() {
+        Ok(())
+    } else {
+        Err(OffsetError::InvalidArgument("the radius has to be positive".to_string()))
+    }
+}
+
+/// Morphological opening: shrinks the polygons by `radius` and expands the result by `radius` again. This removes all parts
+/// that are thinner than twice the radius, like spikes and thin bridges, and rounds convex corners. Holes are preserved.
+///
+/// # Arguments
+///
+/// * `polygons` - A polygon or multipolygon. Exteriors and holes may have any orientation.
+/// * `radius` - The radius of the circle used for the opening. It has to be positive.
+/// * `arcdetail` - Defines how many points should be added in a sharp corner. This number is the number of vertices inserted if it's a full circle.
+///
+/// Returns the opened polygons, with counter-clockwise exteriors and clockwise holes. Fails with `OffsetError::InvalidArgument` if
+/// the radius isn't positive and finite, and otherwise with the same error as [`offset_polygon`](fn.offset_polygon.html).
+pub fn open<N, G>(polygons: G, radius: N, arcdetail: N) -> Result<MultiPolygon<N>, OffsetError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign,
+              G: Into<MultiPolygon<N>> {
+    check_radius(radius)?;
+    Ok(offset_twice(polygons.into(), -radius, arcdetail)?)
+}
+
+/// Morphological closing: expands the polygons by `radius` and shrinks the result by `radius` again. This fills notches and
+/// gaps that are narrower than twice the radius and rounds concave corners. Holes that are large enough are preserved.
+///
+/// # Arguments
+///
+/// * `polygons` - A polygon or multipolygon. Exteriors and holes may have any orientation.
+/// * `radius` - The radius of the circle used for the closing. It has to be positive.
+/// * `arcdetail` - Defines how many points should be added in a sharp corner. This number is the number of vertices inserted if it's a full circle.
+///
+/// Returns the closed polygons, with counter-clockwise exteriors and clockwise holes. Fails with `OffsetError::InvalidArgument` if
+/// the radius isn't positive and finite, and otherwise with the same error as [`offset_polygon`](fn.offset_polygon.html).
+pub fn close<N, G>(polygons: G, radius: N, arcdetail: N) -> Result<MultiPolygon<N>, OffsetError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign,
+              G: Into<MultiPolygon<N>> {
+    check_radius(radius)?;
+    Ok(offset_twice(polygons.into(), radius, arcdetail)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn ring(points: &[(f64, f64)]) -> LineString<f64> {
+        points.iter().map(|(x, y)| Coordinate { x: *x, y: *y }).collect()
+    }
+    fn area(polygon: &Polygon<f64>) -> f64 {
+        std::iter::once(polygon.exterior()).chain(polygon.interiors()).map(|ring| ring.0.windows(2).map(|p| p[0].x * p[1].y - p[1].x * p[0].y).sum::<f64>() / 2.0).sum()
+    }
+    #[test]
+    fn closing() {
+        let input = Polygon::new(ring(&[(0.0, 0.0), (10.0, 0.0), (10.0, 5.0), (5.0, 5.0), (5.0, 10.0), (0.0, 10.0)]), vec![ring(&[(1.0, 1.0), (1.0, 3.0), (3.0, 3.0), (3.0, 1.0)])]);
+        let result = close(input.clone(), 0.4, 16.0).unwrap();
+        assert!(result.0.len() == 1 && result.0[0].interiors().len() == 1, "Closing has to preserve the hole");
+        let exterior = &result.0[0].exterior().0;
+        assert!([(0.0, 0.0), (10.0, 0.0), (10.0, 5.0), (5.0, 10.0), (0.0, 10.0)].iter().all(|(x, y)| {
+            exterior.iter().any(|p| (p.x - x).abs() < 1e-9 && (p.y - y).abs() < 1e-9)
+        }), "Convex corners have to be restored exactly");
+        assert!(exterior.len() == 11, "The arcs of the dilation have to be removed");
+        assert!(!exterior.iter().any(|p| (p.x - 5.0).abs() < 1e-9 && (p.y - 5.0).abs() < 1e-9), "The concave corner has to be rounded");
+
+        let result = close(input, 1.5, 16.0).unwrap();
+        assert!(result.0.len() == 1 && result.0[0].interiors().is_empty(), "Closing has to fill small holes");
+    }
+    #[test]
+    fn opening() {
+        // a square with a thin spike and a hole
+        let input = Polygon::new(ring(&[(0.0, 0.0), (10.0, 0.0), (10.0, 4.9), (15.0, 4.9), (15.0, 5.1), (10.0, 5.1), (10.0, 10.0), (0.0, 10.0)]), vec![ring(&[(4.0, 4.0), (6.0, 4.0), (6.0, 6.0), (4.0, 6.0)])]);
+        let result = open(input, 0.5, 16.0).unwrap();
+        assert!(result.0.len() == 1 && result.0[0].interiors().len() == 1, "Opening has to preserve the hole");
+        assert!(result.0[0].exterior().0.iter().all(|p| p.x < 10.05), "Opening has to remove thin spikes");
+        let hole = &result.0[0].interiors()[0].0;
+        assert!(hole.len() == 5 && hole.iter().all(|p| (p.x - 4.0).abs() < 1e-9 || (p.x - 6.0).abs() < 1e-9), "Hole corners have to be restored exactly");
+    }
+    #[test]
+    fn multipolygon() {
+        let input = MultiPolygon(vec![
+            Polygon::new(ring(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]), vec![]),
+            Polygon::new(ring(&[(4.5, 0.0), (8.5, 0.0), (8.5, 4.0), (4.5, 4.0)]), vec![]),
+        ]);
+        let result = close(input.clone(), 0.5, 16.0).unwrap();
+        // the gap is filled except for a shallow notch left by the rounded corners of the dilation
+        assert!(result.0.len() == 1 && area(&result.0[0]) > 33.9 && area(&result.0[0]) < 34.0, "Closing has to bridge narrow gaps between polygons");
+        let result = open(input, 0.5, 16.0).unwrap();
+        assert!(result.0.len() == 2, "Opening must not merge polygons");
+    }
+    #[test]
+    fn short_edges() {
+        // the edges are shorter than the distance between the vertices an arc collapses into, but have to be kept
+        let circle: LineString<f64> = (0..=500).map(|idx| {
+            let angle = idx as f64 * 2.0 * std::f64::consts::PI / 500.0;
+            Coordinate { x: 5.0 * angle.cos(), y: 5.0 * angle.sin() }
+        }).collect();
+        let result = close(Polygon::new(circle.clone(), vec![]), 0.4, 16.0).unwrap();
+        assert!(result.0.len() == 1 && result.0[0].exterior().0.len() == 501, "Closing has to keep short input edges");
+        let result = open(Polygon::new(circle, vec![]), 0.4, 16.0).unwrap();
+        assert!(result.0.len() == 1 && result.0[0].exterior().0.len() == 501, "Opening has to keep short input edges");
+    }
+    #[test]
+    fn invalid_radius() {
+        let input = Polygon::new(ring(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]), vec![]);
+        assert!(matches!(open(input.clone(), -0.5, 16.0), Err(OffsetError::InvalidArgument(_))), "Negative radius has to be rejected");
+        assert!(matches!(close(input.clone(), 0.0, 16.0), Err(OffsetError::InvalidArgument(_))), "Zero radius has to be rejected");
+        assert!(matches!(close(input, f64::INFINITY, 16.0), Err(OffsetError::InvalidArgument(_))), "Infinite radius has to be rejected");
+    }
+}
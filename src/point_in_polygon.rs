@@ -0,0 +1,155 @@
+//! Point-in-polygon tests based on winding numbers, consistent with how the offset functions classify regions.
+
+use geo_types::{LineString, Coordinate, Polygon, MultiPolygon};
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::ops::{AddAssign, SubAssign};
+
+use super::is_left;
+
+/// The location of a point relative to a polygon.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Location {
+    Inside,
+    Outside,
+    /// The point is within the tolerance of the exterior or of a hole.
+    OnBoundary,
+}
+
+/// Returns how often the closed ring winds around the point counter-clockwise (negative for clockwise). This is the test the
+/// crate uses to select the regions of the raw offset curve. Points within a small distance to the right of an edge (including
+/// points on the edge) count as being on its left side.
+///
+/// # Arguments
+///
+/// * `pt` - The point to test.
+/// * `polygon` - The coordinates of a closed ring (the last coordinate has to be the same as the first coordinate), for example `&line_string.0`.
+// based on http://geomalgorithms.com/a03-_inclusion.html
+pub fn winding_number<N>(pt: Coordinate<N>, polygon: &[Coordinate<N>]) -> isize
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let mut wn = 0;
+    let epsilon = N::from_f32(-0.00001).unwrap(); // oh my
+    for pair in polygon.windows(2) {
+        let (p0, p1) = (pair[0], pair[1]);
+        if p0.y <= pt.y { // start y <= point.y
+            if p1.y > pt.y { // an upwards crossing
+                if is_left(p0, p1, pt) >= epsilon { // point left of edge
+                    wn += 1; // have a valid up intersect
+                }
+            }
+        } else { // start y > point.y
+            if p1.y <= pt.y { // a downward crossing
+                if is_left(p0, p1, pt) < epsilon { // point right of edge
+                    wn -= 1;
+                }
+            }
+        }
+    }
+    wn
+}
+
+/// Returns the winding numbers of all points in respect to the closed ring, see [`winding_number`](fn.winding_number.html).
+pub fn winding_numbers<N>(points: &[Coordinate<N>], polygon: &[Coordinate<N>]) -> Vec<isize>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    points.iter().map(|pt| winding_number(*pt, polygon)).collect()
+}
+
+/// Returns the distance between the point and the segment from `p0` to `p1`.
+pub(crate) fn segment_distance<N>(pt: Coordinate<N>, p0: Coordinate<N>, p1: Coordinate<N>) -> N
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let (dx, dy) = (p1.x - p0.x, p1.y - p0.y);
+    let len = dx * dx + dy * dy;
+    let t = if len > N::zero() {
+        (((pt.x - p0.x) * dx + (pt.y - p0.y) * dy) / len).max(N::zero()).min(N::one())
+    } else {
+        N::zero()
+    };
+    (pt.x - p0.x - t * dx).hypot(pt.y - p0.y - t * dy)
+}
+
+/// Finds out whether the point is inside of the polygon, outside of it or on its boundary. Points are inside when the exterior
+/// winds around them (in any direction) and none of the holes does.
+///
+/// # Arguments
+///
+/// * `pt` - The point to test.
+/// * `polygon` - A polygon with optional holes. The orientation of its rings doesn't matter.
+/// * `tolerance` - Points closer than this distance to any ring of the polygon are on its boundary. Use `0` for an exact test.
+pub fn locate<N>(pt: Coordinate<N>, polygon: &Polygon<N>, tolerance: N) -> Location
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let rings = || std::iter::once(polygon.exterior()).chain(polygon.interiors());
+    if rings().any(|ring| ring.0.windows(2).any(|segment| segment_distance(pt, segment[0], segment[1]) <= tolerance)) {
+        Location::OnBoundary
+    } else if winding_number(pt, &polygon.exterior().0) != 0 && polygon.interiors().iter().all(|hole| winding_number(pt, &hole.0) == 0) {
+        Location::Inside
+    } else {
+        Location::Outside
+    }
+}
+
+/// Like [`locate`](fn.locate.html), but for many points at once.
+pub fn locate_all<N>(points: &[Coordinate<N>], polygon: &Polygon<N>, tolerance: N) -> Vec<Location>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    points.iter().map(|pt| locate(*pt, polygon, tolerance)).collect()
+}
+
+/// Groups rings into polygons by the even-odd rule: rings inside an even number of other rings are exteriors, all others are
+/// holes of the exterior directly around them. `inside(inner, outer)` tells whether the ring with the index `inner` is inside
+/// the one with the index `outer`. Returns the index of every exterior together with the indices of its holes.
+pub(crate) fn nest_by<F>(count: usize, inside: F) -> Vec<(usize, Vec<usize>)>
+        where F: Fn(usize, usize) -> bool {
+    let depths: Vec<usize> = (0..count).map(|idx| (0..count).filter(|&other| other != idx && inside(idx, other)).count()).collect();
+    let mut polygons: Vec<(usize, Vec<usize>)> = (0..count).filter(|&idx| depths[idx] % 2 == 0).map(|idx| (idx, Vec::new())).collect();
+    for idx in (0..count).filter(|&idx| depths[idx] % 2 != 0) {
+        if let Some(polygon) = polygons.iter_mut().find(|(outer, _)| depths[*outer] + 1 == depths[idx] && inside(idx, *outer)) {
+            polygon.1.push(idx);
+        }
+    }
+    polygons
+}
+
+/// Groups closed rings into polygons by the even-odd rule: rings inside an even number of other rings are exteriors, all others
+/// are holes of the exterior directly around them. The rings must not cross each other, their orientation is kept. Empty rings
+/// are left out.
+pub fn nest<N>(rings: Vec<LineString<N>>) -> MultiPolygon<N>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let rings: Vec<LineString<N>> = rings.into_iter().filter(|ring| !ring.0.is_empty()).collect();
+    let polygons = nest_by(rings.len(), |inner, outer| winding_number(rings[inner].0[0], &rings[outer].0) != 0);
+    MultiPolygon(polygons.into_iter().map(|(exterior, holes)| {
+        Polygon::new(rings[exterior].clone(), holes.into_iter().map(|hole| rings[hole].clone()).collect())
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn square(x: f64, y: f64, size: f64) -> LineString<f64> {
+        vec![(x, y), (x + size, y), (x + size, y + size), (x, y + size), (x, y)].into()
+    }
+    #[test]
+    fn winding() {
+        let ring = square(0.0, 0.0, 10.0);
+        assert!(winding_numbers(&[Coordinate { x: 5.0, y: 5.0 }, Coordinate { x: 15.0, y: 5.0 }], &ring.0) == vec![1, 0], "Incorrect winding numbers for square");
+        let mut reversed = ring.clone();
+        reversed.0.reverse();
+        assert!(winding_number(Coordinate { x: 5.0, y: 5.0 }, &reversed.0) == -1, "Clockwise rings have negative winding numbers");
+        assert!(winding_number(Coordinate { x: 5.0, y: 5.0 }, &[]) == 0, "Empty rings don't wind around any point");
+        let empty = Polygon::new(LineString(Vec::new()), Vec::new());
+        assert!(locate(Coordinate { x: 5.0, y: 5.0 }, &empty, 0.1) == Location::Outside, "Points are outside of empty polygons");
+    }
+    #[test]
+    fn holes() {
+        let polygon = Polygon::new(square(0.0, 0.0, 10.0), vec![square(4.0, 4.0, 2.0)]);
+        let points = [
+            Coordinate { x: 1.0, y: 1.0 }, Coordinate { x: 5.0, y: 5.0 }, Coordinate { x: 11.0, y: 5.0 }, Coordinate { x: 10.0, y: 5.0 }, Coordinate { x: 6.05, y: 5.0 },
+        ];
+        assert!(locate_all(&points, &polygon, 0.1) == vec![Location::Inside, Location::Outside, Location::Outside, Location::OnBoundary, Location::OnBoundary], "Incorrect locations with tolerance");
+        assert!(locate(points[4], &polygon, 0.0) == Location::Inside, "Incorrect location without tolerance");
+    }
+    #[test]
+    fn nesting() {
+        let rings = vec![square(4.0, 4.0, 2.0), square(0.0, 0.0, 10.0), square(20.0, 0.0, 1.0), LineString(Vec::new()), square(2.0, 2.0, 6.0)];
+        let polygons = nest(rings);
+        assert!(polygons.0.len() == 3, "Rings inside an even number of rings have to be exteriors");
+        assert!(polygons.0[0].exterior() == &square(4.0, 4.0, 2.0) && polygons.0[1].interiors() == [square(2.0, 2.0, 6.0)] && polygons.0[2].interiors().is_empty(), "Holes have to belong to the ring directly around them");
+    }
+}
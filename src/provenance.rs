@@ -0,0 +1,120 @@
+use geo_types::{LineString, Coordinate};
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::ops::{AddAssign, SubAssign};
+
+use super::{Index, offset_segments, connect_traced, extract_region_indices, intersection_segments, lookup, FillRule, CombinatorialExplosionError};
+
+/// Where a point or segment of the raw offset curve comes from.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Origin {
+    /// The offset of input edge `i`, which goes from coordinate `i` to coordinate `i+1`.
+    Edge(usize),
+    /// The corner at input coordinate `j`, either a point of the arc around it or the coordinate itself.
+    Corner(usize),
+}
+
+/// Where a vertex of the output of [`offset_polygon_with_provenance`](fn.offset_polygon_with_provenance.html) comes from.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Provenance {
+    /// An end point of the offset of input edge `i`, which goes from coordinate `i` to coordinate `i+1`.
+    Edge(usize),
+    /// A point of the arc around input coordinate `j` (or the coordinate itself).
+    Corner(usize),
+    /// The intersection of two segments of the raw offset curve, each given by the input edge or corner it belongs to.
+    Intersection(Origin, Origin),
+}
+
+/// A polygon together with the provenance of each of its coordinates.
+pub type LineStringWithProvenance<N> = (LineString<N>, Vec<Provenance>);
+
+/// Like [`offset_polygon`](fn.offset_polygon.html), but also returns where each vertex of the result comes from. The polygons are
+/// exactly the same as the ones returned by `offset_polygon`.
+///
+/// # Arguments
+///
+/// * `polygon` - A polygon to shrink or expand. It has to be closed (the last coordinate has to be the same as the first coordinate)
+/// * `offset` - A positive number expands the polygon, a negative number shrinks it.
+/// * `arcdetail` - Defines how many points should be added in a sharp corner. This number is the number of vertices inserted if it's a full circle.
+///
+/// Returns a vector of polygons, each with one entry per coordinate that describes where it comes from.
+pub fn offset_polygon_with_provenance<N>(polygon: &LineString<N>, offset: N, arcdetail: N) -> Result<Vec<LineStringWithProvenance<N>>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    if polygon.0.is_empty() {
+        return Ok(vec![(LineString(Vec::new()), Vec::new())]);
+    }
+    let arcstep = N::from_f32(2.0).unwrap() * N::PI() / arcdetail;
+    let vertex_count = polygon.0.len() - 1;
+    // the corner after the last edge is the first coordinate
+    let normalize = |origin: Origin| match origin {
+        Origin::Corner(vertex) => Origin::Corner(vertex % vertex_count),
+        origin => origin,
+    };
+
+    let (connected, origins) = connect_traced(offset_segments(polygon, |_| offset), arcstep);
+    let (regions, cycles, intersections) = extract_region_indices(&connected, std::slice::from_ref(&(0..connected.len())), FillRule::Positive, None)?;
+
+    // segment `i` of the raw offset curve runs from point `i` to point `i+1`
+    let segment_origin = |segment: usize| match (origins[segment], origins[segment+1]) {
+        (_, Origin::Corner(vertex)) | (Origin::Corner(vertex), _) => normalize(Origin::Corner(vertex)),
+        (Origin::Edge(edge0), Origin::Edge(edge1)) if edge0 == edge1 => Origin::Edge(edge0),
+        (Origin::Edge(edge0), _) => normalize(Origin::Corner(edge0 + 1)), // connection between two adjacent edges
+    };
+    let intersection_segments = intersection_segments(&cycles[0], intersections.len());
+
+    Ok(regions.into_iter().map(|(region, _)| {
+        let provenance = region.iter().map(|idx| match idx {
+            Index::Connected(idx) => match normalize(origins[*idx]) {
+                Origin::Edge(edge) => Provenance::Edge(edge),
+                Origin::Corner(vertex) => Provenance::Corner(vertex),
+            },
+            Index::Intersection(intersection) => {
+                let segments = &intersection_segments[*intersection];
+                Provenance::Intersection(segment_origin(segments[0]), segment_origin(segments[segments.len()-1]))
+            },
+        }).collect();
+        let coordinates: Vec<Coordinate<N>> = region.into_iter().map(|idx| lookup(idx, &intersections, &connected)).collect();
+        (LineString(coordinates), provenance)
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::offset_polygon;
+    #[test]
+    fn rectangle() {
+        let input: LineString<f64> = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 5.0), (0.0, 5.0), (0.0, 0.0)].into();
+        let result = offset_polygon_with_provenance(&input, 1.0, 8.0).unwrap();
+        assert!(result.len() == 1 && result[0].0 == offset_polygon(&input, 1.0, 8.0).unwrap()[0], "Output has to be the same as offset_polygon's");
+        let (line, provenance) = &result[0];
+        assert!(line.0.len() == provenance.len(), "Every vertex needs a provenance");
+        assert!(line.0.iter().zip(provenance).all(|(p, provenance)| match provenance {
+            Provenance::Edge(0) => (p.y + 1.0).abs() < 1e-12,
+            Provenance::Edge(1) => (p.x - 11.0).abs() < 1e-12,
+            Provenance::Edge(2) => (p.y - 6.0).abs() < 1e-12,
+            Provenance::Edge(3) => (p.x + 1.0).abs() < 1e-12,
+            Provenance::Corner(vertex) => {
+                let corner = input.0[*vertex];
+                ((p.x - corner.x).hypot(p.y - corner.y) - 1.0).abs() < 1e-12
+            },
+            Provenance::Edge(_) | Provenance::Intersection(..) => false, // expanding a convex polygon doesn't intersect anything
+        }), "Incorrect provenance of the expanded rectangle");
+        assert!(provenance.iter().filter(|provenance| **provenance == Provenance::Corner(0)).count() == 1, "Corner arcs have to refer to their input vertex");
+    }
+    #[test]
+    fn intersections() {
+        let input: LineString<f64> = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 5.0), (0.0, 5.0), (0.0, 0.0)].into();
+        let result = offset_polygon_with_provenance(&input, -1.0, 8.0).unwrap();
+        let (line, provenance) = &result[0];
+        assert!(result.len() == 1 && line.0.len() == 5, "Shrunk rectangle should be one rectangle");
+        // each corner with the two input edges that meet there
+        let corners = [((9.0, 1.0), (0, 1)), ((9.0, 4.0), (1, 2)), ((1.0, 4.0), (2, 3)), ((1.0, 1.0), (3, 0))];
+        assert!(line.0.iter().zip(provenance).all(|(p, provenance)| {
+            let edges = corners.iter().find(|((x, y), _)| (p.x - x).abs() < 1e-12 && (p.y - y).abs() < 1e-12).map(|(_, edges)| *edges);
+            match (provenance, edges) {
+                (Provenance::Intersection(Origin::Edge(edge0), Origin::Edge(edge1)), Some((a, b))) => (*edge0, *edge1) == (a, b) || (*edge0, *edge1) == (b, a),
+                _ => false,
+            }
+        }), "Corners of the shrunk rectangle are intersections of the offset edges meeting there");
+    }
+}
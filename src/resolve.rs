@@ -0,0 +1,85 @@
+use geo_types::{LineString, MultiPolygon};
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::ops::{AddAssign, SubAssign};
+
+use super::{offset_polygons, extract_filled_regions, assemble_polygons, FillRule, CombinatorialExplosionError};
+
+/// Splits a ring that intersects itself into simple loops. The areas enclosed by the ring are selected by their winding number,
+/// in the same way as [`offset_polygon_with_fill_rule`](fn.offset_polygon_with_fill_rule.html) selects the regions of the raw
+/// offset curve.
+///
+/// # Arguments
+///
+/// * `ring` - The ring to resolve. It may be closed or not.
+/// * `fill_rule` - Selects the areas by their winding number, for example [`FillRule::NonZero`](enum.FillRule.html) keeps every area the ring winds around.
+///
+/// Returns simple polygons with counter-clockwise exteriors and clockwise holes. Loops that only touch each other are returned as
+/// separate polygons. The error is the same as the one of [`offset_polygon`](fn.offset_polygon.html).
+pub fn resolve_self_intersections<N>(ring: &LineString<N>, fill_rule: FillRule) -> Result<MultiPolygon<N>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    if ring.0.len() < 2 {
+        return Ok(MultiPolygon(Vec::new()));
+    }
+    let mut curve = ring.0.clone();
+    if curve.first() != curve.last() {
+        curve.push(curve[0]);
+    }
+    let regions = extract_filled_regions(vec![curve], fill_rule, None)?;
+    Ok(MultiPolygon(assemble_polygons(regions.into_iter().map(|(region, _)| region.0).collect())))
+}
+
+/// Like [`offset_polygon`](fn.offset_polygon.html), but for rings that may intersect themselves. The ring is resolved into simple
+/// polygons by [`resolve_self_intersections`](fn.resolve_self_intersections.html) first, which are then offset together. This way
+/// the result only depends on the area selected by the fill rule, not on the order in which the ring runs through its loops.
+///
+/// # Arguments
+///
+/// * `ring` - The ring to offset. It may be closed or not, and its orientation doesn't matter.
+/// * `offset` - A positive number expands the area, a negative number shrinks it.
+/// * `arcdetail` - Defines how many points should be added in a sharp corner. This number is the number of vertices inserted if it's a full circle.
+/// * `fill_rule` - Selects the areas enclosed by the ring by their winding number.
+///
+/// Returns polygons with counter-clockwise exteriors and clockwise holes. The error is the same as the one of
+/// [`offset_polygon`](fn.offset_polygon.html).
+pub fn offset_self_intersecting<N>(ring: &LineString<N>, offset: N, arcdetail: N, fill_rule: FillRule) -> Result<MultiPolygon<N>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let arcstep = N::from_f32(2.0).unwrap() * N::PI() / arcdetail;
+    offset_polygons(&resolve_self_intersections(ring, fill_rule)?, offset, arcstep)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo_types::Coordinate;
+    fn pentagram() -> LineString<f64> {
+        let mut ring: LineString<f64> = (0..5).map(|idx| {
+            let angle = std::f64::consts::FRAC_PI_2 + (idx * 2) as f64 * 2.0 * std::f64::consts::PI / 5.0;
+            Coordinate { x: 10.0 * angle.cos(), y: 10.0 * angle.sin() }
+        }).collect();
+        ring.0.push(ring.0[0]);
+        ring
+    }
+    #[test]
+    fn resolve() {
+        let bowtie: LineString<f64> = vec![(0.0, 0.0), (4.0, 4.0), (4.0, 0.0), (0.0, 4.0), (0.0, 0.0)].into();
+        assert!(resolve_self_intersections(&bowtie, FillRule::NonZero).unwrap().0.len() == 2, "Both loops of a figure-eight have to be kept");
+        assert!(resolve_self_intersections(&bowtie, FillRule::Positive).unwrap().0.len() == 1, "Only the counter-clockwise loop has a positive winding number");
+
+        let result = resolve_self_intersections(&pentagram(), FillRule::NonZero).unwrap();
+        assert!(result.0.len() == 1 && result.0[0].interiors().is_empty() && result.0[0].exterior().0.len() == 11, "Pentagram has to be resolved into its outline");
+        // the tips only touch each other at their corners, so the boundary runs around the outline and the pentagon in the center
+        let result = resolve_self_intersections(&pentagram(), FillRule::EvenOdd).unwrap();
+        assert!(result.0.len() == 1 && result.0[0].interiors().len() == 1 && result.0[0].interiors()[0].0.len() == 6, "Pentagram with even-odd rule consists of its tips");
+    }
+    #[test]
+    fn offset() {
+        let bowtie: LineString<f64> = vec![(0.0, 0.0), (4.0, 4.0), (4.0, 0.0), (0.0, 4.0), (0.0, 0.0)].into();
+        let result = offset_self_intersecting(&bowtie, 0.5, 16.0, FillRule::NonZero).unwrap();
+        assert!(result.0.len() == 1, "Expanded loops of a figure-eight have to be merged");
+        let mut reversed = bowtie.clone();
+        reversed.0.reverse();
+        let (a, b) = (offset_self_intersecting(&bowtie, -0.2, 16.0, FillRule::NonZero).unwrap(), offset_self_intersecting(&reversed, -0.2, 16.0, FillRule::NonZero).unwrap());
+        let area = |polygons: &MultiPolygon<f64>| polygons.0.iter().map(|polygon| polygon.exterior().0.windows(2).map(|p| p[0].x * p[1].y - p[1].x * p[0].y).sum::<f64>() / 2.0).sum::<f64>();
+        assert!(a.0.len() == 2 && b.0.len() == 2 && (area(&a) - area(&b)).abs() < 1e-9, "The result must not depend on the direction of the ring");
+    }
+}
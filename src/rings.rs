@@ -0,0 +1,100 @@
+use geo_types::{LineString, Coordinate, MultiPolygon};
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::ops::{AddAssign, SubAssign};
+
+use super::{raw_offset_curve, extract_filled_regions, assemble_polygons, signed_area, FillRule, CombinatorialExplosionError};
+
+/// Builds the raw offset curve of a single ring and drops the loops it forms in the corners. The result keeps the orientation of
+/// the ring, so clockwise rings result in clockwise rings with a winding number of -1 inside.
+fn resolved_offset<N>(ring: &LineString<N>, offset: N, arcstep: N) -> Result<Vec<Vec<Coordinate<N>>>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let area = signed_area(&ring.0);
+    if area == N::zero() {
+        return Ok(Vec::new());
+    }
+    let clockwise = area.is_sign_negative();
+    // a clockwise ring is offset like the counter-clockwise one in the opposite direction, so that positive offsets always move
+    // it to its right
+    let curve = raw_offset_curve(ring, if clockwise { -offset } else { offset }, arcstep, false);
+    Ok(extract_filled_regions(vec![curve], FillRule::Positive, None)?.into_iter().map(|(region, _)| {
+        let mut region = region.0;
+        if clockwise {
+            region.reverse();
+        }
+        region
+    }).collect())
+}
+
+/// Offsets several rings at once and combines the results. The raw offset curve of every ring is built and the loops it forms
+/// in the corners are dropped, then the intersections of all offset rings are resolved together and the areas are selected by
+/// the fill rule applied to the sum of their winding numbers. With [`FillRule::Positive`](enum.FillRule.html) overlapping rings
+/// are merged, clockwise rings inside of others cut holes into them.
+///
+/// # Arguments
+///
+/// * `rings` - The rings to offset. They may be closed or not and overlap each other. Counter-clockwise rings count as +1, clockwise rings as -1.
+/// * `offset` - A positive number moves every ring to its right (expanding counter-clockwise rings), a negative number to its left.
+/// * `arcdetail` - Defines how many points should be added in a sharp corner. This number is the number of vertices inserted if it's a full circle.
+/// * `fill_rule` - Selects the areas of the result by their winding number.
+///
+/// Returns the selected areas as polygons with counter-clockwise exteriors and clockwise holes. The error is the same as the one
+/// of [`offset_polygon`](fn.offset_polygon.html).
+pub fn offset_rings<N>(rings: &[LineString<N>], offset: N, arcdetail: N, fill_rule: FillRule) -> Result<MultiPolygon<N>, CombinatorialExplosionError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let arcstep = N::from_f32(2.0).unwrap() * N::PI() / arcdetail;
+    let mut curves = Vec::new();
+    for ring in rings {
+        curves.extend(resolved_offset(ring, offset, arcstep)?);
+    }
+    let regions = extract_filled_regions(curves, fill_rule, None)?;
+    Ok(MultiPolygon(assemble_polygons(regions.into_iter().map(|(region, _)| region.0).collect())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo_types::Polygon;
+    fn square(x: f64, y: f64, size: f64) -> LineString<f64> {
+        vec![(x, y), (x + size, y), (x + size, y + size), (x, y + size), (x, y)].into()
+    }
+    fn area(polygon: &Polygon<f64>) -> f64 {
+        std::iter::once(polygon.exterior()).chain(polygon.interiors()).map(|ring| ring.0.windows(2).map(|p| p[0].x * p[1].y - p[1].x * p[0].y).sum::<f64>() / 2.0).sum()
+    }
+    #[test]
+    fn merge() {
+        let input = vec![square(0.0, 0.0, 4.0), square(5.0, 0.0, 4.0)];
+        let result = offset_rings(&input, 1.0, 16.0, FillRule::Positive).unwrap();
+        assert!(result.0.len() == 1 && result.0[0].interiors().is_empty(), "Overlapping offsets have to be merged");
+        let separate = offset_rings(&input, 0.25, 16.0, FillRule::Positive).unwrap();
+        assert!(separate.0.len() == 2, "Offsets that don't overlap have to stay separate");
+    }
+    #[test]
+    fn fill_rules() {
+        let input = vec![square(0.0, 0.0, 4.0), square(2.0, 2.0, 4.0)];
+        let result = offset_rings(&input, 0.0, 16.0, FillRule::NonZero).unwrap();
+        assert!(result.0.len() == 1 && (area(&result.0[0]) - 28.0).abs() < 1e-9, "Incorrect nonzero fill");
+        let result = offset_rings(&input, 0.0, 16.0, FillRule::AtLeast(2)).unwrap();
+        assert!(result.0.len() == 1 && (area(&result.0[0]) - 4.0).abs() < 1e-9, "Incorrect fill of areas covered twice");
+        let result = offset_rings(&input, 0.0, 16.0, FillRule::EvenOdd).unwrap();
+        assert!((result.0.iter().map(area).sum::<f64>() - 24.0).abs() < 1e-9, "Incorrect even-odd fill");
+    }
+    #[test]
+    fn clockwise_hole() {
+        let mut hole = square(3.0, 3.0, 4.0);
+        hole.0.reverse();
+        let result = offset_rings(&[square(0.0, 0.0, 10.0), hole], 1.0, 16.0, FillRule::Positive).unwrap();
+        assert!(result.0.len() == 1 && result.0[0].interiors().len() == 1, "Clockwise rings have to cut holes");
+        let hole = &result.0[0].interiors()[0];
+        assert!(hole.0.len() == 5 && hole.0.iter().all(|p| p.x > 4.0 - 1e-9 && p.x < 6.0 + 1e-9), "Holes have to shrink when the polygon expands");
+    }
+    #[test]
+    fn shrink() {
+        for fill_rule in [FillRule::NonZero, FillRule::EvenOdd, FillRule::Positive] {
+            let result = offset_rings(&[square(0.0, 0.0, 10.0)], -1.0, 16.0, fill_rule).unwrap();
+            assert!(result.0.len() == 1 && (area(&result.0[0]) - 64.0).abs() < 1e-9, "The corners of shrunk rings must not leave loops behind");
+        }
+        let mut hole = square(0.0, 0.0, 10.0);
+        hole.0.reverse();
+        assert!(offset_rings(&[hole], 1.0, 16.0, FillRule::Positive).unwrap().0.is_empty(), "A clockwise ring on its own has no positive area");
+    }
+}
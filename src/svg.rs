@@ -0,0 +1,422 @@
+//! Reading rings from SVG path data and writing rings as SVG documents.
+//!
+//! Only available with the `svg` feature. SVG's y axis points down, coordinates are used as they are in both directions.
+
+use geo_types::{LineString, Coordinate};
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::fmt::Write;
+
+use super::{BezierSegment, flatten_bezier, OffsetTrace, ParseError};
+
+/// The colour of the input rings in [`overlay`](fn.overlay.html).
+pub const INPUT_COLOUR: &str = "#1f77b4";
+/// The colour of the output rings in [`overlay`](fn.overlay.html) and [`document`](fn.document.html).
+pub const OUTPUT_COLOUR: &str = "#d62728";
+/// The colour of the regions that were not kept in [`trace_document`](fn.trace_document.html).
+pub const REJECTED_COLOUR: &str = "#7f7f7f";
+
+/// Reads the numbers, flags and commands of path data.
+struct Tokens<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn error<T>(&self, message: &str) -> Result<T, ParseError> {
+        Err(ParseError {
+            position: self.position,
+            message: message.to_string(),
+        })
+    }
+
+    fn skip_separators(&mut self) {
+        while self.position < self.data.len() && (self.data[self.position].is_ascii_whitespace() || self.data[self.position] == b',') {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_separators();
+        self.data.get(self.position).cloned()
+    }
+
+    fn command(&mut self) -> Option<u8> {
+        match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() && c != b'e' && c != b'E' => {
+                self.position += 1;
+                Some(c)
+            },
+            _ => None,
+        }
+    }
+
+    fn number<N: FromPrimitive>(&mut self) -> Result<N, ParseError> {
+        self.skip_separators();
+        let start = self.position;
+        let digits = |tokens: &mut Self| {
+            while tokens.position < tokens.data.len() && tokens.data[tokens.position].is_ascii_digit() {
+                tokens.position += 1;
+            }
+        };
+        if self.position < self.data.len() && (self.data[self.position] == b'-' || self.data[self.position] == b'+') {
+            self.position += 1;
+        }
+        digits(self);
+        if self.position < self.data.len() && self.data[self.position] == b'.' {
+            self.position += 1;
+            digits(self);
+        }
+        if self.position < self.data.len() && (self.data[self.position] == b'e' || self.data[self.position] == b'E') {
+            self.position += 1;
+            if self.position < self.data.len() && (self.data[self.position] == b'-' || self.data[self.position] == b'+') {
+                self.position += 1;
+            }
+            digits(self);
+        }
+        // the slice only contains ASCII characters
+        let text = std::str::from_utf8(&self.data[start..self.position]).unwrap();
+        match text.parse::<f64>().ok().and_then(N::from_f64) {
+            Some(number) => Ok(number),
+            None => {
+                self.position = start;
+                self.error("Expected a number")
+            },
+        }
+    }
+
+    /// Reads an arc flag, which doesn't need a separator before the next number.
+    fn flag(&mut self) -> Result<bool, ParseError> {
+        match self.peek() {
+            Some(b'0') => { self.position += 1; Ok(false) },
+            Some(b'1') => { self.position += 1; Ok(true) },
+            _ => self.error("Expected a flag"),
+        }
+    }
+
+    fn coordinate<N: Num + Copy + NumCast + PartialOrd + FromPrimitive>(&mut self, relative_to: Option<Coordinate<N>>) -> Result<Coordinate<N>, ParseError> {
+        let (x, y) = (self.number::<N>()?, self.number::<N>()?);
+        Ok(match relative_to {
+            Some(origin) => Coordinate { x: origin.x + x, y: origin.y + y },
+            None => Coordinate { x, y },
+        })
+    }
+}
+
+/// Converts an SVG elliptical arc into lines that are nowhere farther than `tolerance` from it, following the endpoint to center
+/// conversion of the SVG specification.
+#[allow(clippy::too_many_arguments)]
+fn flatten_arc<N>(start: Coordinate<N>, rx: N, ry: N, rotation: N, large_arc: bool, sweep: bool, end: Coordinate<N>, tolerance: N, segments: &mut Vec<BezierSegment<N>>)
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if rx == N::zero() || ry == N::zero() {
+        segments.push(BezierSegment::Line { start, end });
+        return;
+    }
+    let two = N::from_f32(2.0).unwrap();
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let (dx, dy) = ((start.x - end.x) / two, (start.y - end.y) / two);
+    let (x1, y1) = (cos * dx + sin * dy, cos * dy - sin * dx);
+    // radii that are too small are scaled up until the arc fits
+    let lambda = x1 * x1 / (rx * rx) + y1 * y1 / (ry * ry);
+    if lambda > N::one() {
+        rx = rx * lambda.sqrt();
+        ry = ry * lambda.sqrt();
+    }
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coefficient = (numerator / denominator).max(N::zero()).sqrt();
+    if large_arc == sweep {
+        coefficient = -coefficient;
+    }
+    let (cx1, cy1) = (coefficient * rx * y1 / ry, -coefficient * ry * x1 / rx);
+    let center = Coordinate {
+        x: cos * cx1 - sin * cy1 + (start.x + end.x) / two,
+        y: sin * cx1 + cos * cy1 + (start.y + end.y) / two,
+    };
+    let start_angle = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+    let mut delta = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx) - start_angle;
+    let full = two * N::PI();
+    if sweep && delta < N::zero() {
+        delta = delta + full;
+    } else if !sweep && delta > N::zero() {
+        delta = delta - full;
+    }
+
+    let point = |angle: N| Coordinate {
+        x: center.x + cos * rx * angle.cos() - sin * ry * angle.sin(),
+        y: center.y + sin * rx * angle.cos() + cos * ry * angle.sin(),
+    };
+    // a chord of an arc with the angle `step` is at most `r * (1 - cos(step / 2))` away from the arc
+    let radius = rx.max(ry);
+    let max_step = if tolerance < radius { two * (N::one() - tolerance / radius).acos() } else { N::FRAC_PI_2() };
+    let count = (delta.abs() / max_step.min(N::FRAC_PI_2())).ceil().max(N::one());
+    let mut previous = start;
+    for idx in 1..=<usize as NumCast>::from(count).unwrap() {
+        let next = if N::from(idx).unwrap() >= count { end } else { point(start_angle + delta * N::from(idx).unwrap() / count) };
+        segments.push(BezierSegment::Line { start: previous, end: next });
+        previous = next;
+    }
+}
+
+/// Parses SVG path data (the `d` attribute of a `path` element) into closed rings. Every subpath becomes one ring, also if it's
+/// not closed explicitly with `Z`. Curves and arcs are flattened so that the rings are nowhere farther than `tolerance` from them.
+///
+/// All commands of SVG 1.1 are supported in their absolute and relative forms. Subpaths without an area are skipped. A tolerance
+/// that isn't positive is reported as an error at position 0.
+pub fn parse_path<N>(d: &str, tolerance: N) -> Result<Vec<LineString<N>>, ParseError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    if tolerance <= N::zero() || tolerance.is_nan() {
+        return Err(ParseError { position: 0, message: "The tolerance has to be positive".to_string() });
+    }
+    let mut tokens = Tokens { data: d.as_bytes(), position: 0 };
+    let mut contours: Vec<Vec<BezierSegment<N>>> = Vec::new();
+    let mut contour: Vec<BezierSegment<N>> = Vec::new();
+    let origin = Coordinate { x: N::zero(), y: N::zero() };
+    let (mut current, mut subpath_start) = (origin, origin);
+    // the reflected control point for the smooth curve commands
+    let mut last_control: Option<(u8, Coordinate<N>)> = None;
+    let mut command = None;
+
+    loop {
+        let next = match tokens.command() {
+            Some(c) => c,
+            None if tokens.peek().is_none() => break,
+            None => match command {
+                // repeated parameters continue the previous command, after a move as lines
+                Some(b'M') => b'L',
+                Some(b'm') => b'l',
+                Some(c) if c != b'Z' && c != b'z' => c,
+                _ => return tokens.error("Expected a command"),
+            },
+        };
+        command = Some(next);
+        let relative = if next.is_ascii_lowercase() { Some(current) } else { None };
+        let reflected = |kind: u8| match last_control {
+            Some((previous, control)) if previous == kind => Coordinate { x: current.x + current.x - control.x, y: current.y + current.y - control.y },
+            _ => current,
+        };
+        let mut control = None;
+        match next.to_ascii_uppercase() {
+            b'M' => {
+                if !contour.is_empty() {
+                    contours.push(std::mem::take(&mut contour));
+                }
+                current = tokens.coordinate(relative)?;
+                subpath_start = current;
+            },
+            b'L' => {
+                let end = tokens.coordinate(relative)?;
+                contour.push(BezierSegment::Line { start: current, end });
+                current = end;
+            },
+            b'H' => {
+                let x = tokens.number::<N>()?;
+                let end = Coordinate { x: if relative.is_some() { current.x + x } else { x }, y: current.y };
+                contour.push(BezierSegment::Line { start: current, end });
+                current = end;
+            },
+            b'V' => {
+                let y = tokens.number::<N>()?;
+                let end = Coordinate { x: current.x, y: if relative.is_some() { current.y + y } else { y } };
+                contour.push(BezierSegment::Line { start: current, end });
+                current = end;
+            },
+            b'Z' => {
+                if current != subpath_start {
+                    contour.push(BezierSegment::Line { start: current, end: subpath_start });
+                }
+                if !contour.is_empty() {
+                    contours.push(std::mem::take(&mut contour));
+                }
+                current = subpath_start;
+            },
+            b'Q' | b'T' => {
+                let quadratic_control = if next.eq_ignore_ascii_case(&b'Q') { tokens.coordinate(relative)? } else { reflected(b'Q') };
+                let end = tokens.coordinate(relative)?;
+                contour.push(BezierSegment::Quadratic { start: current, control: quadratic_control, end });
+                control = Some((b'Q', quadratic_control));
+                current = end;
+            },
+            b'C' | b'S' => {
+                let control1 = if next.eq_ignore_ascii_case(&b'C') { tokens.coordinate(relative)? } else { reflected(b'C') };
+                let control2 = tokens.coordinate(relative)?;
+                let end = tokens.coordinate(relative)?;
+                contour.push(BezierSegment::Cubic { start: current, control1, control2, end });
+                control = Some((b'C', control2));
+                current = end;
+            },
+            b'A' => {
+                let (rx, ry, rotation) = (tokens.number::<N>()?, tokens.number::<N>()?, tokens.number::<N>()?);
+                let (large_arc, sweep) = (tokens.flag()?, tokens.flag()?);
+                let end = tokens.coordinate(relative)?;
+                flatten_arc(current, rx, ry, rotation, large_arc, sweep, end, tolerance, &mut contour);
+                current = end;
+            },
+            _ => {
+                tokens.position -= 1;
+                return tokens.error("Unknown command");
+            },
+        }
+        last_control = control;
+    }
+    if !contour.is_empty() {
+        contours.push(contour);
+    }
+    let mut rings = Vec::with_capacity(contours.len());
+    for contour in contours {
+        let ring = flatten_bezier(&contour, tolerance).map_err(|error| ParseError { position: d.len(), message: error.to_string().trim_end_matches('.').to_string() })?;
+        if ring.0.len() > 3 {
+            rings.push(ring);
+        }
+    }
+    Ok(rings)
+}
+
+/// Writes rings as SVG path data, one closed subpath per ring.
+pub fn path_data<N>(rings: &[LineString<N>]) -> String
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let mut data = String::new();
+    for ring in rings {
+        // a closing coordinate is replaced by `Z`
+        let count = if ring.0.len() > 1 && ring.0.first() == ring.0.last() { ring.0.len() - 1 } else { ring.0.len() };
+        for (idx, p) in ring.0.iter().take(count).enumerate() {
+            let separator = if data.is_empty() { "" } else { " " };
+            write!(data, "{}{}{} {}", separator, if idx == 0 { "M" } else { "L" }, p.x.to_f64().unwrap(), p.y.to_f64().unwrap()).unwrap();
+        }
+        if count > 0 {
+            data.push_str(" Z");
+        }
+    }
+    data
+}
+
+/// Starts an SVG document with a view box fitted to the points. Returns the document and the margin around the points, which is
+/// a twentieth of their extent.
+fn open_document<'a, N, I>(points: I) -> (String, f64)
+        where N: Num + Copy + NumCast + PartialOrd + Float + 'a, I: Iterator<Item = &'a Coordinate<N>> {
+    let bounds = points.fold(None, |bounds: Option<(f64, f64, f64, f64)>, p| {
+        let (x, y) = (p.x.to_f64().unwrap(), p.y.to_f64().unwrap());
+        Some(match bounds {
+            Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+            None => (x, y, x, y),
+        })
+    });
+    let (x0, y0, x1, y1) = bounds.unwrap_or((0.0, 0.0, 1.0, 1.0));
+    let margin = (x1 - x0).max(y1 - y0).max(1e-9) * 0.05;
+    let document = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+        x0 - margin, y0 - margin, x1 - x0 + 2.0 * margin, y1 - y0 + 2.0 * margin,
+    );
+    (document, margin)
+}
+
+/// Builds an SVG document showing all the layers, each given as rings with a stroke colour and a fill opacity.
+fn layers_document<N>(layers: &[(&[LineString<N>], &str, f64)]) -> String
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let (mut document, _) = open_document(layers.iter().flat_map(|(rings, _, _)| rings.iter()).flat_map(|ring| ring.0.iter()));
+    for (rings, colour, opacity) in layers {
+        writeln!(
+            document,
+            "  <path d=\"{}\" fill=\"{}\" fill-opacity=\"{}\" fill-rule=\"evenodd\" stroke=\"{}\" stroke-width=\"1\" vector-effect=\"non-scaling-stroke\"/>",
+            path_data(rings), colour, opacity, colour,
+        ).unwrap();
+    }
+    document.push_str("</svg>\n");
+    document
+}
+
+/// Writes rings as an SVG document. The view box is fitted to the rings, holes are drawn with the even-odd rule.
+pub fn document<N>(rings: &[LineString<N>]) -> String
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    layers_document(&[(rings, OUTPUT_COLOUR, 0.3)])
+}
+
+/// Writes the input and output of an offset into one SVG document, the input drawn as an outline in
+/// [`INPUT_COLOUR`](constant.INPUT_COLOUR.html) under the filled output in [`OUTPUT_COLOUR`](constant.OUTPUT_COLOUR.html).
+pub fn overlay<N>(input: &[LineString<N>], output: &[LineString<N>]) -> String
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    layers_document(&[(input, INPUT_COLOUR, 0.0), (output, OUTPUT_COLOUR, 0.3)])
+}
+
+/// Draws the stages of an offset recorded by [`offset_polygon_traced`](../fn.offset_polygon_traced.html): the raw offset curve
+/// in [`INPUT_COLOUR`](constant.INPUT_COLOUR.html), the kept regions filled in [`OUTPUT_COLOUR`](constant.OUTPUT_COLOUR.html),
+/// the rejected regions dashed in [`REJECTED_COLOUR`](constant.REJECTED_COLOUR.html), the intersections as dots and the winding
+/// number of every region at the point where it was calculated. Hovering over an element shows its details.
+pub fn trace_document<N>(trace: &OffsetTrace<N>) -> String
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let (mut document, margin) = open_document(trace.raw_curve.0.iter());
+    let number = |value: N| value.to_f64().unwrap();
+    writeln!(
+        document,
+        "  <path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"1\" vector-effect=\"non-scaling-stroke\"><title>raw offset curve, {} points</title></path>",
+        path_data(std::slice::from_ref(&trace.raw_curve)), INPUT_COLOUR, trace.raw_curve.0.len(),
+    ).unwrap();
+    for (idx, region) in trace.regions.iter().enumerate() {
+        let (colour, opacity, dash) = if region.kept { (OUTPUT_COLOUR, 0.3, "none") } else { (REJECTED_COLOUR, 0.0, "4 2") };
+        writeln!(
+            document,
+            "  <path d=\"{}\" fill=\"{}\" fill-opacity=\"{}\" stroke=\"{}\" stroke-width=\"1\" stroke-dasharray=\"{}\" vector-effect=\"non-scaling-stroke\"><title>region {}, {}</title></path>",
+            path_data(std::slice::from_ref(&region.ring)), colour, opacity, colour, dash, idx, if region.kept { "kept" } else { "rejected" },
+        ).unwrap();
+    }
+    for (idx, intersection) in trace.intersections.iter().enumerate() {
+        writeln!(
+            document,
+            "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"black\"><title>intersection {} of segments {} and {}</title></circle>",
+            number(intersection.point.x), number(intersection.point.y), margin * 0.1, idx, intersection.segments.0, intersection.segments.1,
+        ).unwrap();
+    }
+    for region in &trace.regions {
+        if let Some((probe, winding_number)) = region.probe {
+            writeln!(
+                document,
+                "  <text x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\" fill=\"{}\">{}</text>",
+                number(probe.x), number(probe.y), margin * 0.6, if region.kept { OUTPUT_COLOUR } else { REJECTED_COLOUR }, winding_number,
+            ).unwrap();
+        }
+    }
+    document.push_str("</svg>\n");
+    document
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offset_polygon;
+    #[test]
+    fn parse() {
+        let rings: Vec<LineString<f64>> = parse_path("M0,0 L10 0 10 10 H0 Z m 20 0 h10 v10 h-10 z", 0.01).unwrap();
+        assert!(rings.len() == 2, "Path data has two subpaths");
+        assert!(rings[0].0 == LineString::from(vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0), (0.0, 0.0)]).0, "Incorrect absolute subpath");
+        assert!(rings[1].0 == LineString::from(vec![(20.0, 0.0), (30.0, 0.0), (30.0, 10.0), (20.0, 10.0), (20.0, 0.0)]).0, "Incorrect relative subpath");
+
+        let circle: Vec<LineString<f64>> = parse_path("M-5 0A5 5 0 0 1 5 0a5,5 0 1,1-10,0z", 0.001).unwrap();
+        assert!(circle.len() == 1 && circle[0].0.len() > 16, "Arcs have to be flattened");
+        assert!(circle[0].0.iter().all(|p| (p.x.hypot(p.y) - 5.0).abs() < 0.001), "Flattened arcs have to be on the circle");
+        let curves: Vec<LineString<f64>> = parse_path("M0 0 C0 5 5 10 10 10 S20 5 20 0 Q15 -5 10 -5 T0 0", 0.01).unwrap();
+        assert!(curves.len() == 1 && curves[0].0.len() > 10, "Curves have to be flattened");
+
+        let error = parse_path::<f64>("M0 0 L10 X", 0.01).unwrap_err();
+        assert!(error.position == 9, "Error has to point to the invalid character");
+        assert!(parse_path::<f64>("M-5 0A5 5 0 0 1 5 0z", 0.0).is_err(), "Zero tolerance has to be rejected");
+        assert!(parse_path::<f64>("M0 0 L10 0 L10 10 Z", -0.1).is_err(), "Negative tolerance has to be rejected");
+    }
+    #[test]
+    fn write() {
+        let input: Vec<LineString<f64>> = parse_path("M0 0 L10 0 L10 10 L0 10 Z", 0.01).unwrap();
+        assert!(path_data(&input) == "M0 0 L10 0 L10 10 L0 10 Z", "Incorrect path data");
+        let output = offset_polygon(&input[0], 1.0, 8.0).unwrap();
+        assert!(parse_path::<f64>(&path_data(&output), 0.01).unwrap() == output, "Path data has to round-trip");
+        let svg = overlay(&input, &output);
+        assert!(svg.starts_with("<svg") && svg.contains(INPUT_COLOUR) && svg.contains(OUTPUT_COLOUR) && svg.matches("<path").count() == 2, "Overlay has to contain input and output");
+        assert!(svg.contains("viewBox=\"-1.6"), "View box has to fit the output");
+    }
+    #[test]
+    fn trace() {
+        let input: LineString<f64> = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (6.0, 10.0), (6.0, 2.0), (4.0, 2.0), (4.0, 10.0), (0.0, 10.0), (0.0, 0.0)].into();
+        let (_, trace) = crate::offset_polygon_traced(&input, -1.5, 16.0);
+        let svg = trace_document(&trace);
+        assert!(svg.matches("<circle").count() == trace.intersections.len() && svg.matches("<path").count() == trace.regions.len() + 1, "Every stage has to be drawn");
+        assert!(svg.matches(">1</text>").count() == 2 && svg.contains(REJECTED_COLOUR), "Winding numbers have to be shown");
+    }
+}
@@ -0,0 +1,187 @@
+//! Profile toolpaths for milling machines, generated from offsets with true arcs and written as G-code.
+//!
+//! The spindle is assumed to turn clockwise (`M3`). The top of the stock is at `Z0`, cuts go down to negative Z values.
+
+use geo_types::LineString;
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::ops::{AddAssign, SubAssign};
+use std::fmt::Write;
+
+use super::{offset_polygon_arcs, signed_area, PathSegment, Path, OffsetError};
+
+/// Which side of the outline the tool cuts on.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Side {
+    /// The tool runs around the outside of the outline, for cutting out a part.
+    Outside,
+    /// The tool runs along the inside of the outline, for cutting out a hole.
+    Inside,
+}
+
+/// The direction the tool moves relative to the part.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Direction {
+    /// The part is to the right of the tool, it moves clockwise around outside profiles.
+    Climb,
+    /// The part is to the left of the tool, it moves counter-clockwise around outside profiles.
+    Conventional,
+}
+
+/// The settings of a profile toolpath. Lengths are in the units of the outline, rates in those units per minute.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToolpathOptions<N> {
+    pub tool_radius: N,
+    pub side: Side,
+    pub direction: Direction,
+    /// The total depth of the cut below the top of the stock.
+    pub depth: N,
+    /// The maximum depth of a single pass. The depth is split into passes of equal depth.
+    pub step_down: N,
+    /// The height for rapid moves above the stock.
+    pub safe_z: N,
+    /// The feed rate for cutting moves.
+    pub feed_rate: N,
+    /// The feed rate for plunging into the stock.
+    pub plunge_rate: N,
+}
+
+impl<N> ToolpathOptions<N> where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    /// Creates options for an outside climb profile cut in a single pass, with a safe height of 5 and feed rates of 1000 and 300.
+    pub fn new(tool_radius: N, depth: N) -> Self {
+        ToolpathOptions {
+            tool_radius,
+            side: Side::Outside,
+            direction: Direction::Climb,
+            depth,
+            step_down: depth,
+            safe_z: N::from_f32(5.0).unwrap(),
+            feed_rate: N::from_f32(1000.0).unwrap(),
+            plunge_rate: N::from_f32(300.0).unwrap(),
+        }
+    }
+
+    /// Checks that the tool radius, the depths, the safe height and the feed rates are positive and finite. Fails with
+    /// `OffsetError::InvalidArgument` naming the first invalid setting otherwise.
+    pub fn validate(&self) -> Result<(), OffsetError> {
+        let settings = [
+            ("tool radius", self.tool_radius), ("depth", self.depth), ("step down", self.step_down), ("safe height", self.safe_z),
+            ("feed rate", self.feed_rate), ("plunge rate", self.plunge_rate),
+        ];
+        match settings.iter().find(|(_, value)| !value.is_finite() || *value <= N::zero()) {
+            Some((name, _)) => Err(OffsetError::InvalidArgument(format!("the {} has to be positive", name))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the paths of the tool center for cutting along the outline. Outside profiles expand the outline by the tool radius,
+/// inside profiles shrink it, and the paths are oriented for the cut direction. The outline may be oriented either way. Fails if
+/// the options are invalid, see [`ToolpathOptions::validate`](struct.ToolpathOptions.html#method.validate), and otherwise with
+/// the same error as [`offset_polygon_arcs`](../fn.offset_polygon_arcs.html).
+pub fn profile<N>(outline: &LineString<N>, options: &ToolpathOptions<N>) -> Result<Vec<Path<N>>, OffsetError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    options.validate()?;
+    let mut outline = outline.clone();
+    if signed_area(&outline.0) < N::zero() {
+        outline.0.reverse();
+    }
+    let offset = if options.side == Side::Outside { options.tool_radius } else { -options.tool_radius };
+    // the offset paths have the area around the part on their left for outside profiles, and the part on their right for inside profiles
+    let reverse = (options.side == Side::Outside) == (options.direction == Direction::Climb);
+    Ok(offset_polygon_arcs(&outline, offset)?.into_iter().map(|path| {
+        if reverse {
+            path.iter().rev().map(PathSegment::reversed).collect()
+        } else {
+            path
+        }
+    }).collect())
+}
+
+fn format<N: Float>(value: N) -> String {
+    let value = value.to_f64().unwrap();
+    // avoid writing "-0.0000"
+    format!("{:.4}", if value.abs() < 0.00005 { 0.0 } else { value })
+}
+
+/// Writes G-code that cuts along the paths in depth passes. Every pass starts with a rapid move to the start of the path at the
+/// safe height and a plunge, lines are cut with `G1` and arcs with `G2` (clockwise) or `G3` (counter-clockwise) with the center
+/// given relative to the start of the arc. The tool returns to the safe height after every pass. Fails if the options are invalid.
+pub fn gcode<N>(paths: &[Path<N>], options: &ToolpathOptions<N>) -> Result<String, OffsetError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    options.validate()?;
+    let mut code = String::new();
+    writeln!(code, "G90 G17").unwrap();
+    writeln!(code, "G0 Z{}", format(options.safe_z)).unwrap();
+    let passes = (options.depth / options.step_down).ceil().max(N::one());
+    for path in paths.iter().filter(|path| !path.is_empty()) {
+        let start = path[0].start();
+        for pass in 1..=<usize as NumCast>::from(passes).unwrap() {
+            let z = -options.depth * N::from(pass).unwrap() / passes;
+            writeln!(code, "G0 X{} Y{}", format(start.x), format(start.y)).unwrap();
+            writeln!(code, "G1 Z{} F{}", format(z), format(options.plunge_rate)).unwrap();
+            for (idx, segment) in path.iter().enumerate() {
+                let (end, feed) = (segment.end(), if idx == 0 { format!(" F{}", format(options.feed_rate)) } else { String::new() });
+                match *segment {
+                    PathSegment::Line { .. } => writeln!(code, "G1 X{} Y{}{}", format(end.x), format(end.y), feed).unwrap(),
+                    PathSegment::Arc { center, start_angle, end_angle, .. } => {
+                        let start = segment.start();
+                        writeln!(
+                            code, "{} X{} Y{} I{} J{}{}", if end_angle < start_angle { "G2" } else { "G3" },
+                            format(end.x), format(end.y), format(center.x - start.x), format(center.y - start.y), feed,
+                        ).unwrap();
+                    },
+                }
+            }
+            writeln!(code, "G0 Z{}", format(options.safe_z)).unwrap();
+        }
+    }
+    writeln!(code, "M2").unwrap();
+    Ok(code)
+}
+
+/// Generates the G-code for a profile cut along the outline, see [`profile`](fn.profile.html) and [`gcode`](fn.gcode.html).
+pub fn profile_gcode<N>(outline: &LineString<N>, options: &ToolpathOptions<N>) -> Result<String, OffsetError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    gcode(&profile(outline, options)?, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn outside() {
+        let square: LineString<f64> = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0), (0.0, 0.0)].into();
+        let options = ToolpathOptions { step_down: 1.0, ..ToolpathOptions::new(1.5, 3.0) };
+        let code = profile_gcode(&square, &options).unwrap();
+        assert!(code.matches("G0 Z5.0000").count() == 4 && code.contains("G1 Z-1.0000 F300.0000") && code.contains("G1 Z-3.0000 F300.0000"), "Cut has to be split into three passes");
+        assert!(code.matches("G2 ").count() == 12 && !code.contains("G3 "), "Climb milling goes clockwise around the part");
+        assert!(code.contains("G2 X0.0000 Y11.5000 I1.5000 J0.0000"), "Arcs have to be around the corners");
+        assert!(code.ends_with("M2\n"), "Program has to end");
+
+        let reversed: LineString<f64> = square.0.iter().rev().cloned().collect::<Vec<_>>().into();
+        let conventional = ToolpathOptions { direction: Direction::Conventional, ..options };
+        let code = profile_gcode(&reversed, &conventional).unwrap();
+        assert!(code.matches("G3 ").count() == 12 && !code.contains("G2 "), "Conventional milling goes counter-clockwise around the part");
+    }
+    #[test]
+    fn inside() {
+        let notch: LineString<f64> = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (6.0, 10.0), (6.0, 4.0), (4.0, 4.0), (4.0, 10.0), (0.0, 10.0), (0.0, 0.0)].into();
+        let options = ToolpathOptions { side: Side::Inside, ..ToolpathOptions::new(0.5, 1.0) };
+        let paths = profile(&notch, &options).unwrap();
+        assert!(paths.len() == 1 && !path_is_clockwise(&paths[0]), "Climb milling goes counter-clockwise inside a hole");
+        let code = gcode(&paths, &options).unwrap();
+        assert!(code.matches("G2 ").count() == 2 && code.contains("G0 X") && code.matches("G1 Z-1.0000").count() == 1, "Concave corners of the hole have to be arcs");
+        assert!(profile(&notch, &ToolpathOptions { side: Side::Inside, ..ToolpathOptions::new(6.0, 1.0) }).unwrap().is_empty(), "Tool too large for the hole");
+    }
+    #[test]
+    fn invalid_options() {
+        let square: LineString<f64> = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0), (0.0, 0.0)].into();
+        for options in [ToolpathOptions { step_down: 0.0, ..ToolpathOptions::new(1.5, 3.0) }, ToolpathOptions::new(1.5, -3.0), ToolpathOptions::new(0.0, 3.0), ToolpathOptions { safe_z: -5.0, ..ToolpathOptions::new(1.5, 3.0) }] {
+            assert!(matches!(profile_gcode(&square, &options), Err(OffsetError::InvalidArgument(_))), "Invalid options have to be reported");
+        }
+        assert!(gcode(&[], &ToolpathOptions { step_down: f64::NAN, ..ToolpathOptions::new(1.5, 3.0) }).is_err(), "Invalid step down has to be reported");
+    }
+    fn path_is_clockwise(path: &[PathSegment<f64>]) -> bool {
+        crate::path_area(path) < 0.0
+    }
+}
@@ -0,0 +1,87 @@
+use geo_types::{LineString, Coordinate};
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+use std::ops::{AddAssign, SubAssign};
+
+use super::{offset_segments, connect, extract_filled_regions, FillRule, CombinatorialExplosionError};
+
+/// A self-intersection of the raw offset curve.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceIntersection<N: Num + Copy + NumCast + PartialOrd> {
+    pub point: Coordinate<N>,
+    /// The two segments of the raw offset curve that intersect, segment `i` runs from point `i` to point `i+1`.
+    pub segments: (usize, usize),
+}
+
+/// A region the raw offset curve was split into.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceRegion<N: Num + Copy + NumCast + PartialOrd> {
+    /// The closed boundary of the region.
+    pub ring: LineString<N>,
+    /// The center point of the first segment of the region that isn't (almost) horizontal, together with its winding number in
+    /// respect to the raw offset curve. Regions without such a segment don't have one.
+    pub probe: Option<(Coordinate<N>, isize)>,
+    /// Whether the region is part of the result, which is the case if the winding number is positive on exactly one side of it.
+    pub kept: bool,
+}
+
+/// The intermediate stages of [`offset_polygon_traced`](fn.offset_polygon_traced.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct OffsetTrace<N: Num + Copy + NumCast + PartialOrd> {
+    /// The offset edges connected by the arcs around the corners, before its self-intersections are resolved.
+    pub raw_curve: LineString<N>,
+    pub intersections: Vec<TraceIntersection<N>>,
+    /// All regions of the raw offset curve, in the order they were traced. Empty if the intersections couldn't be resolved.
+    pub regions: Vec<TraceRegion<N>>,
+}
+
+/// Like [`offset_polygon`](fn.offset_polygon.html), but also records how the result was found: the raw offset curve, its
+/// self-intersections, the regions they split it into and the winding number that decided whether each region was kept.
+///
+/// # Arguments
+///
+/// * `polygon` - A polygon to shrink or expand. It has to be closed (the last coordinate has to be the same as the first coordinate)
+/// * `offset` - A positive number expands the polygon, a negative number shrinks it.
+/// * `arcdetail` - Defines how many points should be added in a sharp corner. This number is the number of vertices inserted if it's a full circle.
+///
+/// Returns the result of `offset_polygon` and the trace. The trace is returned in case of an error as well, with all intersections
+/// found until then.
+pub fn offset_polygon_traced<N>(polygon: &LineString<N>, offset: N, arcdetail: N) -> (Result<Vec<LineString<N>>, CombinatorialExplosionError>, OffsetTrace<N>)
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive + AddAssign + SubAssign {
+    let mut trace = OffsetTrace {
+        raw_curve: LineString(Vec::new()),
+        intersections: Vec::new(),
+        regions: Vec::new(),
+    };
+    if polygon.0.is_empty() {
+        return (Ok(vec![LineString(Vec::new())]), trace);
+    }
+    let arcstep = N::from_f32(2.0).unwrap() * N::PI() / arcdetail;
+    let connected = connect(offset_segments(polygon, |_| offset), arcstep);
+    let result = extract_filled_regions(vec![connected], FillRule::Positive, Some(&mut trace))
+        .map(|regions| regions.into_iter().map(|(region, _)| region).collect());
+    (result, trace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::offset_polygon;
+    #[test]
+    fn stages() {
+        // shrinking the notched square splits it into two parts
+        let input: LineString<f64> = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (6.0, 10.0), (6.0, 2.0), (4.0, 2.0), (4.0, 10.0), (0.0, 10.0), (0.0, 0.0)].into();
+        let (result, trace) = offset_polygon_traced(&input, -1.5, 16.0);
+        let result = result.unwrap();
+        assert!(result == offset_polygon(&input, -1.5, 16.0).unwrap() && result.len() == 2, "Result has to be the same as offset_polygon's");
+        assert!(trace.raw_curve.0.first() == trace.raw_curve.0.last() && !trace.intersections.is_empty(), "Raw curve has to be closed and intersect itself");
+        assert!(trace.intersections.iter().all(|intersection| {
+            let (s0, s1) = intersection.segments;
+            let distance = |segment: usize| {
+                let (p0, p1) = (trace.raw_curve.0[segment], trace.raw_curve.0[segment + 1]);
+                ((p1.x - p0.x) * (intersection.point.y - p0.y) - (p1.y - p0.y) * (intersection.point.x - p0.x)).abs() / (p1.x - p0.x).hypot(p1.y - p0.y)
+            };
+            s0 != s1 && distance(s0) < 1e-9 && distance(s1) < 1e-9
+        }), "Intersections have to be on both of their segments");
+        assert!(trace.regions.len() > result.len() && trace.regions.iter().all(|region| region.kept == (region.probe.map(|(_, winding_number)| winding_number) == Some(1))), "Regions have to be kept by their winding number");
+    }
+}
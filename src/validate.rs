@@ -0,0 +1,153 @@
+use geo_types::{LineString, Coordinate};
+use num_traits::{Num, NumCast, float::{Float, FloatConst}, FromPrimitive};
+
+use super::signed_area;
+use super::intersect::{segment_intersection, SegmentIntersection};
+
+/// The direction in which a ring runs around its area.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Orientation {
+    CounterClockwise,
+    Clockwise,
+}
+
+/// A problem found by [`validate_ring`](fn.validate_ring.html). Segment `i` goes from vertex `i` to vertex `i+1`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem<N: Num + Copy + NumCast + PartialOrd> {
+    /// The ring has less than three distinct vertices.
+    TooFewVertices,
+    /// The last coordinate is not the same as the first one.
+    NotClosed,
+    /// The vertex is the same as the one before it.
+    DuplicateVertex {
+        index: usize,
+    },
+    /// Two segments cross or touch each other in a point that is not their shared vertex.
+    SelfIntersection {
+        segments: (usize, usize),
+        point: Coordinate<N>,
+    },
+    /// Two segments are collinear and run along each other between the two points.
+    SelfOverlap {
+        segments: (usize, usize),
+        start: Coordinate<N>,
+        end: Coordinate<N>,
+    },
+    /// The ring doesn't enclose any area.
+    ZeroArea,
+    /// The ring runs in the other direction than expected.
+    WrongOrientation {
+        expected: Orientation,
+    },
+}
+
+/// The result of [`validate_ring`](fn.validate_ring.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationReport<N: Num + Copy + NumCast + PartialOrd> {
+    pub problems: Vec<Problem<N>>,
+    /// The signed area of the ring, positive for counter-clockwise rings.
+    pub area: N,
+    /// The orientation of the ring, `None` if it doesn't have an area.
+    pub orientation: Option<Orientation>,
+}
+
+impl<N> ValidationReport<N> where N: Num + Copy + NumCast + PartialOrd {
+    /// Returns whether no problems were found.
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Checks whether the ring can be offset without surprises: it has to be closed, must not contain duplicate vertices, must not
+/// intersect itself and has to enclose an area.
+///
+/// # Arguments
+///
+/// * `ring` - The ring to check.
+/// * `expected` - The orientation the ring should have, or `None` if it doesn't matter. `offset_polygon` expects counter-clockwise rings.
+/// * `tolerance` - The distance up to which vertices count as duplicates and segments as collinear.
+pub fn validate_ring<N>(ring: &LineString<N>, expected: Option<Orientation>, tolerance: N) -> ValidationReport<N>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FloatConst + FromPrimitive {
+    let mut problems = Vec::new();
+    let vertices = &ring.0;
+    let same = |p0: Coordinate<N>, p1: Coordinate<N>| (p1.x - p0.x).hypot(p1.y - p0.y) <= tolerance;
+    let closed = vertices.len() > 1 && same(vertices[0], vertices[vertices.len()-1]);
+    if !closed {
+        problems.push(Problem::NotClosed);
+    }
+    // treat the ring as closed from here on
+    let count = if closed { vertices.len() - 1 } else { vertices.len() };
+    for index in 1..count {
+        if same(vertices[index-1], vertices[index]) {
+            problems.push(Problem::DuplicateVertex { index });
+        }
+    }
+    let segments: Vec<usize> = (0..count).filter(|idx| !same(vertices[*idx], vertices[(idx+1) % count])).collect();
+    if segments.len() < 3 {
+        problems.push(Problem::TooFewVertices);
+    }
+
+    for (i, first) in segments.iter().enumerate() {
+        for (j, second) in segments.iter().enumerate().skip(i+1) {
+            let adjacent = j == i + 1 || (i == 0 && j == segments.len() - 1);
+            let (a0, a1) = (vertices[*first], vertices[(first+1) % count]);
+            let (b0, b1) = (vertices[*second], vertices[(second+1) % count]);
+            match segment_intersection(a0, a1, b0, b1, tolerance) {
+                Some(SegmentIntersection::Overlap { start, end, .. }) => problems.push(Problem::SelfOverlap { segments: (*first, *second), start, end }),
+                // adjacent segments always touch in their shared vertex
+                Some(SegmentIntersection::Point { point, .. }) if !adjacent || !(same(point, a0) || same(point, a1)) => {
+                    problems.push(Problem::SelfIntersection { segments: (*first, *second), point });
+                },
+                _ => {},
+            }
+        }
+    }
+
+    let area = if count > 0 { signed_area(&vertices[0..count]) } else { N::zero() };
+    let orientation = if area > N::zero() {
+        Some(Orientation::CounterClockwise)
+    } else if area < N::zero() {
+        Some(Orientation::Clockwise)
+    } else {
+        problems.push(Problem::ZeroArea);
+        None
+    };
+    if let (Some(expected), Some(orientation)) = (expected, orientation) {
+        if expected != orientation {
+            problems.push(Problem::WrongOrientation { expected });
+        }
+    }
+
+    ValidationReport {
+        problems,
+        area,
+        orientation,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn valid() {
+        let ring: LineString<f64> = vec![(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0), (0.0, 0.0)].into();
+        let report = validate_ring(&ring, Some(Orientation::CounterClockwise), 1e-9);
+        assert!(report.is_valid() && (report.area - 16.0).abs() < 1e-12, "Square has to be valid");
+        let report = validate_ring(&ring, Some(Orientation::Clockwise), 1e-9);
+        assert!(report.problems == vec![Problem::WrongOrientation { expected: Orientation::Clockwise }], "Orientation has to be checked");
+    }
+    #[test]
+    fn problems() {
+        let ring: LineString<f64> = vec![(0.0, 0.0), (4.0, 4.0), (4.0, 4.0), (4.0, 0.0), (0.0, 4.0)].into();
+        let report = validate_ring(&ring, None, 1e-9);
+        assert!(report.problems.contains(&Problem::NotClosed), "Open ring has to be reported");
+        assert!(report.problems.contains(&Problem::DuplicateVertex { index: 2 }), "Duplicate vertex has to be reported");
+        assert!(report.problems.contains(&Problem::SelfIntersection { segments: (0, 3), point: Coordinate { x: 2.0, y: 2.0 } }), "Self-intersection has to be reported");
+        assert!(report.problems.contains(&Problem::ZeroArea), "Figure-eight with equal loops has no area");
+
+        // a spike that runs back along itself
+        let ring: LineString<f64> = vec![(0.0, 0.0), (4.0, 0.0), (6.0, 0.0), (5.0, 0.0), (4.0, 4.0), (0.0, 0.0)].into();
+        let report = validate_ring(&ring, None, 1e-9);
+        assert!(report.problems.iter().any(|problem| matches!(problem, Problem::SelfOverlap { segments: (1, 2), .. })), "Spike has to be reported as overlap");
+    }
+}
@@ -0,0 +1,451 @@
+//! Reading and writing geometries as Well-Known Text (WKT) and Well-Known Binary (WKB).
+//!
+//! Only available with the `wkt` feature. `LINESTRING`, `POLYGON` and `MULTIPOLYGON` are supported, Z and M values are
+//! accepted when reading and dropped.
+
+use geo_types::{LineString, Coordinate, Polygon, MultiPolygon};
+use num_traits::{Num, NumCast, float::Float, FromPrimitive};
+use std::fmt::Write;
+
+use super::ParseError;
+
+/// A geometry that can be read from and written to WKT and WKB.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Geometry<N: Num + Copy + NumCast + PartialOrd> {
+    LineString(LineString<N>),
+    Polygon(Polygon<N>),
+    MultiPolygon(MultiPolygon<N>),
+}
+
+impl<N: Num + Copy + NumCast + PartialOrd> From<LineString<N>> for Geometry<N> {
+    fn from(line: LineString<N>) -> Self {
+        Geometry::LineString(line)
+    }
+}
+
+impl<N: Num + Copy + NumCast + PartialOrd> From<Polygon<N>> for Geometry<N> {
+    fn from(polygon: Polygon<N>) -> Self {
+        Geometry::Polygon(polygon)
+    }
+}
+
+impl<N: Num + Copy + NumCast + PartialOrd> From<MultiPolygon<N>> for Geometry<N> {
+    fn from(polygons: MultiPolygon<N>) -> Self {
+        Geometry::MultiPolygon(polygons)
+    }
+}
+
+fn error<T>(position: usize, message: &str) -> Result<T, ParseError> {
+    Err(ParseError {
+        position,
+        message: message.to_string(),
+    })
+}
+
+/// Reads the keywords, numbers and parentheses of WKT.
+struct Tokens<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn skip_whitespace(&mut self) {
+        self.position += self.text[self.position..].len() - self.text[self.position..].trim_start().len();
+    }
+
+    fn word(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let rest = &self.text[self.position..];
+        let length = rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '.' && c != '-' && c != '+').unwrap_or(rest.len());
+        self.position += length;
+        &rest[..length]
+    }
+
+    /// Consumes `c` if it's the next character.
+    fn accept(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.text[self.position..].starts_with(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes `EMPTY` if it's the next word.
+    fn accept_empty(&mut self) -> bool {
+        let start = self.position;
+        if self.word().eq_ignore_ascii_case("EMPTY") {
+            true
+        } else {
+            self.position = start;
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.accept(c) {
+            Ok(())
+        } else {
+            error(self.position, &format!("Expected '{}'", c))
+        }
+    }
+
+    /// Reads the optional dimension after a geometry type and returns the number of ordinates per coordinate, or `None` for `EMPTY`.
+    fn dimensions(&mut self) -> Result<Option<usize>, ParseError> {
+        let start = self.position;
+        let dimensions = match self.word().to_ascii_uppercase().as_str() {
+            "" => return Ok(Some(2)),
+            "Z" | "M" => 3,
+            "ZM" => 4,
+            "EMPTY" => return Ok(None),
+            _ => return error(start, "Expected a dimension or EMPTY"),
+        };
+        let start = self.position;
+        match self.word().to_ascii_uppercase().as_str() {
+            "EMPTY" => Ok(None),
+            "" => Ok(Some(dimensions)),
+            _ => error(start, "Expected '('"),
+        }
+    }
+
+    fn coordinate<N: Num + Copy + NumCast + PartialOrd + FromPrimitive>(&mut self, dimensions: usize) -> Result<Coordinate<N>, ParseError> {
+        let mut ordinates = Vec::with_capacity(dimensions);
+        for _ in 0..dimensions {
+            self.skip_whitespace();
+            let start = self.position;
+            match self.word().parse::<f64>().ok().and_then(N::from_f64) {
+                Some(ordinate) => ordinates.push(ordinate),
+                None => return error(start, "Expected a number"),
+            }
+        }
+        let y = ordinates.swap_remove(1);
+        Ok(Coordinate { x: ordinates.swap_remove(0), y })
+    }
+
+    /// Reads a parenthesized list, where `item` reads one element.
+    fn list<T, F: FnMut(&mut Self) -> Result<T, ParseError>>(&mut self, mut item: F) -> Result<Vec<T>, ParseError> {
+        self.expect('(')?;
+        let mut items = vec![item(self)?];
+        while self.accept(',') {
+            items.push(item(self)?);
+        }
+        self.expect(')')?;
+        Ok(items)
+    }
+
+    fn line_string<N>(&mut self, dimensions: usize) -> Result<LineString<N>, ParseError>
+            where N: Num + Copy + NumCast + PartialOrd + FromPrimitive {
+        if self.accept_empty() {
+            return Ok(LineString(Vec::new()));
+        }
+        Ok(LineString(self.list(|tokens| tokens.coordinate(dimensions))?))
+    }
+
+    fn polygon<N>(&mut self, dimensions: usize) -> Result<Polygon<N>, ParseError>
+            where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+        if self.accept_empty() {
+            return Ok(Polygon::new(LineString(Vec::new()), Vec::new()));
+        }
+        let mut rings = self.list(|tokens| tokens.line_string(dimensions))?;
+        let exterior = rings.remove(0);
+        Ok(Polygon::new(exterior, rings))
+    }
+}
+
+/// Parses a `LINESTRING`, `POLYGON` or `MULTIPOLYGON` from WKT. Keywords are case insensitive.
+pub fn read_wkt<N>(text: &str) -> Result<Geometry<N>, ParseError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    let mut tokens = Tokens { text, position: 0 };
+    let kind = tokens.word().to_ascii_uppercase();
+    let start = tokens.position;
+    let dimensions = tokens.dimensions()?;
+    let geometry = match (kind.as_str(), dimensions) {
+        ("LINESTRING", Some(dimensions)) => Geometry::LineString(tokens.line_string(dimensions)?),
+        ("LINESTRING", None) => Geometry::LineString(LineString(Vec::new())),
+        ("POLYGON", Some(dimensions)) => Geometry::Polygon(tokens.polygon(dimensions)?),
+        ("POLYGON", None) => Geometry::Polygon(Polygon::new(LineString(Vec::new()), Vec::new())),
+        ("MULTIPOLYGON", Some(dimensions)) => Geometry::MultiPolygon(MultiPolygon(tokens.list(|tokens| tokens.polygon(dimensions))?)),
+        ("MULTIPOLYGON", None) => Geometry::MultiPolygon(MultiPolygon(Vec::new())),
+        _ => return error(0, "Expected LINESTRING, POLYGON or MULTIPOLYGON"),
+    };
+    tokens.skip_whitespace();
+    if tokens.position < text.len() {
+        return error(tokens.position.max(start), "Unexpected text after geometry");
+    }
+    Ok(geometry)
+}
+
+fn write_coordinates<N>(text: &mut String, line: &LineString<N>)
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    if line.0.is_empty() {
+        text.push_str("EMPTY");
+        return;
+    }
+    text.push('(');
+    for (idx, p) in line.0.iter().enumerate() {
+        write!(text, "{}{} {}", if idx == 0 { "" } else { ", " }, p.x.to_f64().unwrap(), p.y.to_f64().unwrap()).unwrap();
+    }
+    text.push(')');
+}
+
+fn write_polygon<N>(text: &mut String, polygon: &Polygon<N>)
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    if polygon.exterior().0.is_empty() && polygon.interiors().is_empty() {
+        text.push_str("EMPTY");
+        return;
+    }
+    text.push('(');
+    write_coordinates(text, polygon.exterior());
+    for interior in polygon.interiors() {
+        text.push_str(", ");
+        write_coordinates(text, interior);
+    }
+    text.push(')');
+}
+
+/// Writes a geometry as WKT. Numbers are written with as many digits as needed to read back the same values.
+pub fn write_wkt<N>(geometry: &Geometry<N>) -> String
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let mut text = String::new();
+    match geometry {
+        Geometry::LineString(line) if line.0.is_empty() => text.push_str("LINESTRING EMPTY"),
+        Geometry::LineString(line) => {
+            text.push_str("LINESTRING ");
+            write_coordinates(&mut text, line);
+        },
+        Geometry::Polygon(polygon) if polygon.exterior().0.is_empty() && polygon.interiors().is_empty() => text.push_str("POLYGON EMPTY"),
+        Geometry::Polygon(polygon) => {
+            text.push_str("POLYGON ");
+            write_polygon(&mut text, polygon);
+        },
+        Geometry::MultiPolygon(polygons) if polygons.0.is_empty() => text.push_str("MULTIPOLYGON EMPTY"),
+        Geometry::MultiPolygon(polygons) => {
+            text.push_str("MULTIPOLYGON (");
+            for (idx, polygon) in polygons.0.iter().enumerate() {
+                if idx > 0 {
+                    text.push_str(", ");
+                }
+                write_polygon(&mut text, polygon);
+            }
+            text.push(')');
+        },
+    }
+    text
+}
+
+/// Reads the fields of WKB in the byte order given by the geometry.
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+    little_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes<const COUNT: usize>(&mut self) -> Result<[u8; COUNT], ParseError> {
+        if self.position + COUNT > self.data.len() {
+            return error(self.position, "Unexpected end of data");
+        }
+        let mut bytes = [0; COUNT];
+        bytes.copy_from_slice(&self.data[self.position..self.position + COUNT]);
+        self.position += COUNT;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, ParseError> {
+        let bytes = self.bytes::<4>()?;
+        Ok(if self.little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    }
+
+    fn f64(&mut self) -> Result<f64, ParseError> {
+        let bytes = self.bytes::<8>()?;
+        Ok(if self.little_endian { f64::from_le_bytes(bytes) } else { f64::from_be_bytes(bytes) })
+    }
+
+    /// Reads the byte order and the type of a geometry, returns the type without dimensions and the number of ordinates.
+    fn header(&mut self) -> Result<(u32, usize), ParseError> {
+        let start = self.position;
+        self.little_endian = match self.bytes::<1>()? {
+            [0] => false,
+            [1] => true,
+            _ => return error(start, "Invalid byte order"),
+        };
+        let kind = self.u32()?;
+        // EWKB stores the dimensions and the SRID as flags, ISO WKB adds multiples of 1000 to the type
+        let (has_z, has_m, has_srid) = (kind & 0x8000_0000 != 0, kind & 0x4000_0000 != 0, kind & 0x2000_0000 != 0);
+        let kind = kind & 0x0fff_ffff;
+        let (kind, dimensions) = match kind / 1000 {
+            0 => (kind, 2 + has_z as usize + has_m as usize),
+            1 | 2 => (kind % 1000, 3),
+            3 => (kind % 1000, 4),
+            _ => return error(start + 1, "Invalid geometry type"),
+        };
+        if has_srid {
+            self.u32()?;
+        }
+        Ok((kind, dimensions))
+    }
+
+    fn line_string<N: FromPrimitive + Num + Copy + NumCast + PartialOrd>(&mut self, dimensions: usize) -> Result<LineString<N>, ParseError> {
+        let count = self.u32()? as usize;
+        let mut points = Vec::with_capacity(count.min(self.data.len() / 16));
+        for _ in 0..count {
+            let start = self.position;
+            let (x, y) = (self.f64()?, self.f64()?);
+            for _ in 2..dimensions {
+                self.f64()?;
+            }
+            match (N::from_f64(x), N::from_f64(y)) {
+                (Some(x), Some(y)) => points.push(Coordinate { x, y }),
+                _ => return error(start, "Coordinate out of range"),
+            }
+        }
+        Ok(LineString(points))
+    }
+
+    fn polygon<N: FromPrimitive + Num + Copy + NumCast + PartialOrd + Float>(&mut self, dimensions: usize) -> Result<Polygon<N>, ParseError> {
+        let count = self.u32()? as usize;
+        let mut rings = (0..count).map(|_| self.line_string(dimensions)).collect::<Result<Vec<_>, _>>()?;
+        let exterior = if rings.is_empty() { LineString(Vec::new()) } else { rings.remove(0) };
+        Ok(Polygon::new(exterior, rings))
+    }
+}
+
+/// Parses a `LINESTRING`, `POLYGON` or `MULTIPOLYGON` from WKB in either byte order. ISO WKB and PostGIS' EWKB are accepted.
+pub fn read_wkb<N>(data: &[u8]) -> Result<Geometry<N>, ParseError>
+        where N: Num + Copy + NumCast + PartialOrd + Float + FromPrimitive {
+    let mut reader = Reader { data, position: 0, little_endian: true };
+    let geometry = match reader.header()? {
+        (2, dimensions) => Geometry::LineString(reader.line_string(dimensions)?),
+        (3, dimensions) => Geometry::Polygon(reader.polygon(dimensions)?),
+        (6, _) => {
+            let count = reader.u32()? as usize;
+            let mut polygons = Vec::with_capacity(count.min(data.len() / 9));
+            for _ in 0..count {
+                let start = reader.position;
+                match reader.header()? {
+                    (3, dimensions) => polygons.push(reader.polygon(dimensions)?),
+                    _ => return error(start, "Expected a polygon"),
+                }
+            }
+            Geometry::MultiPolygon(MultiPolygon(polygons))
+        },
+        _ => return error(1, "Expected LINESTRING, POLYGON or MULTIPOLYGON"),
+    };
+    if reader.position < data.len() {
+        return error(reader.position, "Unexpected data after geometry");
+    }
+    Ok(geometry)
+}
+
+fn write_line_string<N>(data: &mut Vec<u8>, line: &LineString<N>)
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    data.extend_from_slice(&(line.0.len() as u32).to_le_bytes());
+    for p in &line.0 {
+        data.extend_from_slice(&p.x.to_f64().unwrap().to_le_bytes());
+        data.extend_from_slice(&p.y.to_f64().unwrap().to_le_bytes());
+    }
+}
+
+fn write_wkb_polygon<N>(data: &mut Vec<u8>, polygon: &Polygon<N>)
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    data.push(1);
+    data.extend_from_slice(&3u32.to_le_bytes());
+    // an empty polygon has no rings, but an empty exterior still has to be written before any holes
+    if polygon.exterior().0.is_empty() && polygon.interiors().is_empty() {
+        data.extend_from_slice(&0u32.to_le_bytes());
+        return;
+    }
+    data.extend_from_slice(&(polygon.interiors().len() as u32 + 1).to_le_bytes());
+    write_line_string(data, polygon.exterior());
+    for interior in polygon.interiors() {
+        write_line_string(data, interior);
+    }
+}
+
+/// Writes a geometry as two-dimensional little endian WKB.
+pub fn write_wkb<N>(geometry: &Geometry<N>) -> Vec<u8>
+        where N: Num + Copy + NumCast + PartialOrd + Float {
+    let mut data = Vec::new();
+    match geometry {
+        Geometry::LineString(line) => {
+            data.push(1);
+            data.extend_from_slice(&2u32.to_le_bytes());
+            write_line_string(&mut data, line);
+        },
+        Geometry::Polygon(polygon) => write_wkb_polygon(&mut data, polygon),
+        Geometry::MultiPolygon(polygons) => {
+            data.push(1);
+            data.extend_from_slice(&6u32.to_le_bytes());
+            data.extend_from_slice(&(polygons.0.len() as u32).to_le_bytes());
+            for polygon in &polygons.0 {
+                write_wkb_polygon(&mut data, polygon);
+            }
+        },
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offset_polygon;
+    /// The rectangle with a rounded left side of the offsetting tests.
+    fn complex_input() -> LineString<f64> {
+        LineString(vec![
+            Coordinate { x: 490.0, y: 210.0 }, Coordinate { x: 1260.0, y: 210.0 }, Coordinate { x: 1260.0, y: 433.2142857142857 }, Coordinate { x: 1260.0, y: 538.2142857142858 }, Coordinate { x: 1260.0, y: 1190.0 }, Coordinate { x: 490.0, y: 1190.0 }, Coordinate { x: 484.2650146484375, y: 1189.7904052734375 }, Coordinate { x: 473.10126876831055, y: 1188.4620895385742 }, Coordinate { x: 462.30468368530273, y: 1186.069725036621 }, Coordinate { x: 451.87491607666016, y: 1182.6432037353516 }, Coordinate { x: 441.8116226196289, y: 1178.212417602539 }, Coordinate { x: 432.1144599914551, y: 1172.807258605957 }, Coordinate { x: 422.78308486938477, y: 1166.457618713379 }, Coordinate { x: 413.81715393066406, y: 1159.1933898925781 }, Coordinate { x: 405.21632385253906, y: 1151.0444641113281 }, Coordinate { x: 396.98025131225586, y: 1142.0407333374023 }, Coordinate { x: 389.10859298706055, y: 1132.2120895385742 }, Coordinate { x: 381.6010055541992, y: 1121.5884246826172 }, Coordinate { x: 374.45714569091797, y: 1110.1996307373047 }, Coordinate { x: 367.6766700744629, y: 1098.0755996704102 }, Coordinate { x: 361.2592353820801, y: 1085.246223449707 }, Coordinate { x: 355.2044982910156, y: 1071.7413940429688 }, Coordinate { x: 349.5121154785156, y: 1057.5910034179688 }, Coordinate { x: 344.1817436218262, y: 1042.8249435424805 }, Coordinate { x: 339.21303939819336, y: 1027.4731063842773 }, Coordinate { x: 334.6056594848633, y: 1011.5653839111328 }, Coordinate { x: 330.35926055908203, y: 995.1316680908203 }, Coordinate { x: 326.4734992980957, y: 978.2018508911133 }, Coordinate { x: 322.9480323791504, y: 960.8058242797852 }, Coordinate { x: 319.7825164794922, y: 942.9734802246094 }, Coordinate { x: 316.9766082763672, y: 924.7347106933594 }, Coordinate { x: 314.5299644470215, y: 906.1194076538086 }, Coordinate { x: 312.4422416687012, y: 887.1574630737305 }, Coordinate { x: 310.71309661865234, y: 867.8787689208984 }, Coordinate { x: 308.7462463378906, y: 838.4624633789062 }, Coordinate { x: 307.435302734375, y: 798.36328125 }, Coordinate { x: 307.552490234375, y: 757.53515625 }, Coordinate { x: 309.0950622558594, y: 716.2172241210938 }, Coordinate { x: 312.0602722167969, y: 674.6486206054688 }, Coordinate { x: 316.44537353515625, y: 633.0684814453125 }, Coordinate { x: 322.24761962890625, y: 591.7159423828125 }, Coordinate { x: 327.5717887878418, y: 561.0149459838867 }, Coordinate { x: 331.53319549560547, y: 540.7335662841797 }, Coordinate { x: 335.8473434448242, y: 520.6436004638672 }, Coordinate { x: 340.51388931274414, y: 500.77494049072266 }, Coordinate { x: 345.5324897766113, y: 481.15747833251953 }, Coordinate { x: 350.9028015136719, y: 461.82110595703125 }, Coordinate { x: 356.6244812011719, y: 442.79571533203125 }, Coordinate { x: 362.6971855163574, y: 424.11119842529297 }, Coordinate { x: 369.1205711364746, y: 405.79744720458984 }, Coordinate { x: 375.89429473876953, y: 387.8843536376953 }, Coordinate { x: 383.0180130004883, y: 370.4018096923828 }, Coordinate { x: 390.49138259887695, y: 353.3797073364258 }, Coordinate { x: 398.31406021118164, y: 336.84793853759766 }, Coordinate { x: 406.48570251464844, y: 320.8363952636719 }, Coordinate { x: 415.00596618652344, y: 305.3749694824219 }, Coordinate { x: 423.87450790405273, y: 290.4935531616211 }, Coordinate { x: 433.0909843444824, y: 276.22203826904297 }, Coordinate { x: 442.6550521850586, y: 262.59031677246094 }, Coordinate { x: 452.56636810302734, y: 249.62828063964844 }, Coordinate { x: 462.82458877563477, y: 237.3658218383789 }, Coordinate { x: 473.42937088012695, y: 225.83283233642578 }, Coordinate { x: 484.38037109375, y: 215.0592041015625 }, Coordinate { x: 490.0, y: 210.0 },
+        ])
+    }
+    fn round_trip(geometry: Geometry<f64>) {
+        assert!(read_wkt::<f64>(&write_wkt(&geometry)).unwrap() == geometry, "Geometry has to round-trip through WKT");
+        assert!(read_wkb::<f64>(&write_wkb(&geometry)).unwrap() == geometry, "Geometry has to round-trip through WKB");
+    }
+    #[test]
+    fn fixtures() {
+        let triangle = read_wkt::<f64>("POLYGON ((0 0, 1 0, 1 1, 0 0))").unwrap();
+        assert!(triangle == Geometry::Polygon(Polygon::new(vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 0.0)].into(), vec![])), "Incorrect triangle");
+        let rectangle = read_wkt::<f64>("linestring(910 840,1890 840,1890 1190,910 1190,910 840)").unwrap();
+        let rectangle = match rectangle {
+            Geometry::LineString(line) => line,
+            _ => panic!("Rectangle has to be a line string"),
+        };
+        assert!(rectangle == vec![(910.0, 840.0), (1890.0, 840.0), (1890.0, 1190.0), (910.0, 1190.0), (910.0, 840.0)].into(), "Incorrect rectangle");
+        let complex = complex_input();
+
+        for input in [triangle.clone(), Geometry::LineString(rectangle.clone()), Geometry::LineString(complex.clone())] {
+            round_trip(input);
+        }
+        // the offset results as a multipolygon with a hole
+        let polygons: Vec<Polygon<f64>> = [rectangle, complex].iter().map(|line| {
+            let result = offset_polygon(line, 4.0, 20.0).unwrap();
+            Polygon::new(result[0].clone(), vec![line.clone().into_iter().rev().collect()])
+        }).collect();
+        round_trip(Geometry::MultiPolygon(MultiPolygon(polygons)));
+    }
+    #[test]
+    fn variants() {
+        let z = read_wkt::<f64>("MULTIPOLYGON Z (((0 0 1, 1 0 2, 1 1 3, 0 0 1)), ((5 5 0, 6 5 0, 6 6 0, 5 5 0), (5.2 5.1 0, 5.8 5.7 0, 5.8 5.1 0, 5.2 5.1 0)))").unwrap();
+        match &z {
+            Geometry::MultiPolygon(polygons) => assert!(polygons.0.len() == 2 && polygons.0[1].interiors().len() == 1, "Incorrect multipolygon"),
+            _ => panic!("Expected a multipolygon"),
+        }
+        assert!(read_wkt::<f64>("POLYGON EMPTY").unwrap() == Geometry::Polygon(Polygon::new(LineString(vec![]), vec![])), "Incorrect empty polygon");
+        // holes of a polygon without an exterior and empty polygons in a multipolygon
+        let hole: LineString<f64> = vec![(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (0.0, 0.0)].into();
+        round_trip(Geometry::Polygon(Polygon::new(LineString(vec![]), vec![hole.clone()])));
+        round_trip(Geometry::MultiPolygon(MultiPolygon(vec![Polygon::new(LineString(vec![]), vec![]), Polygon::new(hole.clone(), vec![])])));
+
+        // big endian ISO WKB with Z values
+        let mut data = vec![0];
+        data.extend_from_slice(&1002u32.to_be_bytes());
+        data.extend_from_slice(&2u32.to_be_bytes());
+        for value in &[1.0f64, 2.0, 3.0, 4.0, 5.0, 6.0] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        assert!(read_wkb::<f64>(&data).unwrap() == Geometry::LineString(vec![(1.0, 2.0), (4.0, 5.0)].into()), "Incorrect big endian WKB");
+
+        let error = read_wkt::<f64>("POLYGON ((0 0, 1 x, 0 0))").unwrap_err();
+        assert!(error.position == 17, "Error has to point to the invalid number");
+        assert!(read_wkb::<f64>(&data[..20]).is_err(), "Truncated WKB has to fail");
+    }
+}